@@ -1,5 +1,9 @@
 const grpc = require('@grpc/grpc-js');
 const protoLoader = require('@grpc/proto-loader');
+const {Web3} = require('web3');
+
+const OracleProofABI = require('../../resources/oracleProof..json');
+const SignedCoherentClusterABI = require('../../resources/signedCoherentCluster.json');
 
 class PullServiceClient {
   constructor(address) {
@@ -16,9 +20,37 @@ class PullServiceClient {
     this.client = new pullProto.PullService(address, grpc.credentials.createSsl());
   }
 
+  // Set `request.round` to fetch the proof of a past round. A server that keeps no history
+  // answers with its latest proof instead, so the round of the returned proof is checked.
   getProof(request, callback) {
-    this.client.getProof(request, callback);
+    this.client.getProof(request, (err, response) => {
+      if (err || request.round === undefined || request.round === null || !response.evm) {
+        callback(err, response);
+        return;
+      }
+      const round = String(request.round);
+      const other = proofRounds(response.evm).find((proofRound) => proofRound !== round);
+      if (other !== undefined) {
+        callback(roundMismatch(round, other));
+        return;
+      }
+      callback(null, response);
+    });
   }
 }
 
+// Rounds of the clusters in an EVM proof, as decimal strings.
+function proofRounds(evm) {
+  const web3 = new Web3();
+  const proof = web3.eth.abi.decodeParameters(OracleProofABI, web3.utils.bytesToHex(evm.proof_bytes));
+  return proof[0].clustersRaw.map((raw) =>
+    web3.eth.abi.decodeParameters(SignedCoherentClusterABI, raw)[0].round.toString(10));
+}
+
+function roundMismatch(round, other) {
+  const err = new Error(`asked for the proof of round ${round}, got one of round ${other}; the server may keep no history`);
+  err.details = err.message;
+  return err;
+}
+
 module.exports = PullServiceClient;
\ No newline at end of file
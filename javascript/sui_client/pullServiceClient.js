@@ -16,9 +16,47 @@ class PullServiceClient {
     this.client = new pullProto.PullService(address, grpc.credentials.createSsl());
   }
 
+  // Set `request.round` to fetch the proof of a past round. A server that keeps no history
+  // answers with its latest proof instead, so the round of the returned proof is checked.
   getProof(request, callback) {
-    this.client.getProof(request, callback);
+    this.client.getProof(request, (err, response) => {
+      if (err || request.round === undefined || request.round === null || !response.sui) {
+        callback(err, response);
+        return;
+      }
+      const round = String(request.round);
+      const other = sccRounds(response.sui.scc_round).find((proofRound) => proofRound !== round);
+      if (other !== undefined) {
+        callback(roundMismatch(round, other));
+        return;
+      }
+      callback(null, response);
+    });
+  }
+}
+
+// Decodes `scc_round`, a BCS vector<u64>: a ULEB128 length followed by little-endian words.
+function sccRounds(bytes) {
+  let len = 0;
+  let offset = 0;
+  for (let shift = 0; ; shift += 7) {
+    const byte = bytes[offset++];
+    len += (byte & 0x7f) * 2 ** shift;
+    if (!(byte & 0x80)) {
+      break;
+    }
   }
+  const rounds = [];
+  for (let i = 0; i < len; ++i, offset += 8) {
+    rounds.push(bytes.readBigUInt64LE(offset).toString(10));
+  }
+  return rounds;
+}
+
+function roundMismatch(round, other) {
+  const err = new Error(`asked for the proof of round ${round}, got one of round ${other}; the server may keep no history`);
+  err.details = err.message;
+  return err;
 }
 
 module.exports = PullServiceClient;
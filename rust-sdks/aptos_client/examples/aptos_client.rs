@@ -1,4 +1,7 @@
-use aptos_pull_client::aptos_connector::{invoke_aptos_chain, AptosConfig, AptosConnector};
+use aptos_pull_client::aptos_connector::{
+    invoke_aptos_chain, AptosConfig, AptosConnector, Submission,
+};
+use aptos_pull_client::chain_type::ChainType;
 use aptos_pull_client::pull_service::pull_response::Resp;
 use aptos_pull_client::{pull_service, Client};
 
@@ -10,7 +13,8 @@ async fn main() {
     // Create a PullRequest
     let request = pull_service::PullRequest {
         pair_indexes: vec![0, 21, 61, 49], // Set the pair indexes as an array
-        chain_type: "aptos".to_string(),
+        chain_type: ChainType::Aptos.to_string(),
+        round: None, // Set a past round to fetch, or None for the latest
     };
 
     // Call the get_proof function and handle the result
@@ -26,14 +30,19 @@ async fn main() {
 
 async fn call_contract(input: pull_service::PullResponse) {
     if let Some(Resp::Aptos(aptos_payload)) = input.resp {
-        let aptos_connector = AptosConnector::new(AptosConfig::new(
-            "<--secret-key-->",
-            "<--rpc-url-->",
-            "<-contract-address-->",
-            50000,
-        ))
-        .await
-        .unwrap();
-        invoke_aptos_chain(aptos_payload, aptos_connector).await
+        let aptos_config = AptosConfig::builder()
+            .secret_key("<--secret-key-->")
+            .client_url("<--rpc-url-->")
+            .sc_address("<-contract-address-->")
+            .gas_budget(50000)
+            .gas_estimate(1.5) // Simulate first and use 1.5x the simulated gas, capped at the budget
+            .build()
+            .unwrap();
+        let aptos_connector = AptosConnector::new(aptos_config).await.unwrap();
+        match invoke_aptos_chain(aptos_payload, aptos_connector).await {
+            Ok(Submission::Sent(tx_hash)) => println!("{tx_hash}"),
+            Ok(Submission::DryRun { tx_hash, .. }) => println!("dry run, not submitted: {tx_hash}"),
+            Err(e) => eprintln!("{e}"),
+        }
     }
 }
@@ -1,38 +1,253 @@
+use crate::env::Env;
 use crate::errors::ConnectorError;
 use crate::pull_service::PullResponseAptos;
 use aptos::common::utils::{chain_id, get_sequence_number};
-use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
+use aptos_sdk::crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
+use aptos_sdk::crypto::HashValue;
 use aptos_sdk::move_types::account_address::AccountAddress;
 use aptos_sdk::move_types::identifier::Identifier;
 use aptos_sdk::move_types::language_storage::ModuleId;
-use aptos_sdk::rest_client::Client;
+use aptos_sdk::rest_client::{Client, Transaction};
 use aptos_sdk::transaction_builder::TransactionFactory;
-use aptos_sdk::types::LocalAccount;
-use aptos_types::transaction::{EntryFunction, SignedTransaction, TransactionPayload};
-use ed25519_dalek::{PublicKey, SecretKey};
+use aptos_types::transaction::{
+    EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
+};
+use async_trait::async_trait;
+use ed25519_dalek::SecretKey;
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::{fmt::Display, future::Future};
+use std::time::Duration;
 
 use tiny_keccak::{Hasher, Sha3};
 
 const MODULE: &str = "<CONTRACT MODULE>"; // Module name of your contract. Ex. pull_example
 const ENTRY: &str = "<CONTRACT FUNCTION>"; // Module function name of your contract. Ex. get_pair_price
 
-pub async fn invoke_aptos_chain(payload: PullResponseAptos, aptos_connector: AptosConnector) {
-    let account = Account::from_secret_key(aptos_connector.secret_key).unwrap();
+/// Hash of a submitted Aptos transaction.
+pub type TransactionHash = HashValue;
 
-    let address = AccountAddress::from_hex_literal(&aptos_connector.sc_addr).unwrap();
+/// What a submission did: submitted the transaction, or, with [`AptosConfig::with_dry_run`],
+/// only signed it.
+#[derive(Clone, Debug)]
+pub enum Submission<T> {
+    Sent(T),
+    /// The signed transaction that would have been submitted.
+    DryRun {
+        tx_hash: TransactionHash,
+        transaction: SignedTransaction,
+    },
+}
+
+impl<T> Submission<T> {
+    /// What was submitted, `None` for a dry run.
+    pub fn sent(self) -> Option<T> {
+        match self {
+            Submission::Sent(sent) => Some(sent),
+            Submission::DryRun { .. } => None,
+        }
+    }
+}
+
+pub async fn invoke_aptos_chain(
+    payload: PullResponseAptos,
+    aptos_connector: AptosConnector,
+) -> Result<Submission<TransactionHash>, ConnectorError> {
+    let max_gas = aptos_connector.gas_budget;
+    invoke_aptos_chain_with_gas(payload, aptos_connector, max_gas).await
+}
+
+/// Submits `payload` with a max gas amount of `max_gas` in place of the configured `gas_budget`.
+/// With [`AptosConfig::with_gas_estimate`], `max_gas` caps the estimate instead.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(
+            pair_indexes = ?payload.pair_indexes,
+            contract = %aptos_connector.sc_addr,
+            gas_budget = max_gas
+        )
+    )
+)]
+pub async fn invoke_aptos_chain_with_gas(
+    payload: PullResponseAptos,
+    aptos_connector: AptosConnector,
+    max_gas: u64,
+) -> Result<Submission<TransactionHash>, ConnectorError> {
+    let tx = build_transaction(payload, &aptos_connector, max_gas).await?;
+    let tx_hash = tx.clone().committed_hash();
+    if aptos_connector.dry_run {
+        return Ok(dry_run(tx_hash, tx));
+    }
+
+    let response = aptos_connector
+        .client
+        .with_aptos(|aptos| aptos.submit_and_wait(&tx))
+        .await
+        .map_err(|e| ConnectorError::Submission(e.to_string()))?
+        .into_inner();
+    check_execution(tx_hash, &response)?;
+    Ok(Submission::Sent(tx_hash))
+}
+
+/// Submits `payload` like [`invoke_aptos_chain`], then polls the fullnode for the transaction
+/// until it is committed, for at most `timeout`.
+///
+/// Fails with [`ConnectorError::MoveAbort`] if the contract aborted, with
+/// [`ConnectorError::Execution`] if the transaction failed otherwise, and with
+/// [`ConnectorError::ConfirmationTimeout`] if it is not committed in time. A dry run returns
+/// [`Submission::DryRun`] instead of submitting.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(pair_indexes = ?payload.pair_indexes, contract = %aptos_connector.sc_addr)
+    )
+)]
+pub async fn invoke_aptos_chain_and_wait(
+    payload: PullResponseAptos,
+    aptos_connector: AptosConnector,
+    timeout: Duration,
+) -> Result<Submission<ExecutionStatus>, ConnectorError> {
+    let max_gas = aptos_connector.gas_budget;
+    let tx = build_transaction(payload, &aptos_connector, max_gas).await?;
+    let tx_hash = tx.clone().committed_hash();
+    if aptos_connector.dry_run {
+        return Ok(dry_run(tx_hash, tx));
+    }
+
+    submit_and_poll(&tx, &aptos_connector, timeout)
+        .await
+        .map(Submission::Sent)
+}
+
+fn dry_run<T>(tx_hash: TransactionHash, transaction: SignedTransaction) -> Submission<T> {
+    log::info!("aptos: dry run, not submitting transaction {tx_hash}");
+    Submission::DryRun {
+        tx_hash,
+        transaction,
+    }
+}
+
+/// A submission with its sequence number, gas and expiration filled in, waiting to be signed.
+pub type UnsignedTx = RawTransaction;
+
+/// Builds the submission of `payload` without signing it, for a key held on a machine without
+/// network access. The transaction expires `expires_in` from now, so leave enough time to carry
+/// it to the signer and back.
+///
+/// Sign the result with [`Signer::sign`], e.g. on a [`PrivateKeySigner`], and submit it with
+/// [`broadcast_aptos_chain`].
+pub async fn build_unsigned_aptos_chain(
+    payload: PullResponseAptos,
+    aptos_connector: &AptosConnector,
+    expires_in: Duration,
+) -> Result<UnsignedTx, ConnectorError> {
+    let max_gas = aptos_connector.gas_budget;
+    build_raw_transaction(payload, aptos_connector, max_gas, Some(expires_in)).await
+}
+
+/// Submits a transaction signed elsewhere, then waits for it like
+/// [`invoke_aptos_chain_and_wait`]. The connector's own signer is not used, so a
+/// [watch-only](AptosConnector::watch_only) one will do.
+pub async fn broadcast_aptos_chain(
+    signed: &SignedTransaction,
+    aptos_connector: &AptosConnector,
+    timeout: Duration,
+) -> Result<ExecutionStatus, ConnectorError> {
+    submit_and_poll(signed, aptos_connector, timeout).await
+}
+
+async fn submit_and_poll(
+    tx: &SignedTransaction,
+    aptos_connector: &AptosConnector,
+    timeout: Duration,
+) -> Result<ExecutionStatus, ConnectorError> {
+    let tx_hash = tx.clone().committed_hash();
+    aptos_connector
+        .client
+        .with_aptos(|aptos| aptos.submit(tx))
+        .await
+        .map_err(|e| ConnectorError::Submission(e.to_string()))?;
+    log::debug!("aptos: pull proof submitted in transaction {tx_hash}");
+
+    let committed = tokio::time::timeout(timeout, async {
+        loop {
+            match aptos_connector
+                .client
+                .with_aptos(|aptos| aptos.get_transaction_by_hash(tx_hash))
+                .await
+            {
+                Ok(response) if !response.inner().is_pending() => break response.into_inner(),
+                Ok(_) => {}
+                // A load-balanced fullnode may not have seen the transaction yet.
+                Err(e) => log::debug!("aptos: transaction {tx_hash} not found yet: {e}"),
+            }
+            tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+        }
+    })
+    .await
+    .map_err(|_| ConnectorError::ConfirmationTimeout { tx_hash })?;
+    check_execution(tx_hash, &committed)
+}
+
+/// How often [`invoke_aptos_chain_and_wait`] asks the fullnode about the transaction.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A committed pull proof transaction, as reported by the fullnode.
+#[derive(Clone, Debug)]
+pub struct ExecutionStatus {
+    pub tx_hash: TransactionHash,
+    /// Ledger version the transaction was committed at.
+    pub version: u64,
+    pub gas_used: u64,
+    /// The node's description of the outcome, `Executed successfully` for a successful one.
+    pub vm_status: String,
+}
+
+/// Signs the entry function call carrying `payload`. During a dry run this also simulates it,
+/// so the caller only has to skip the submission.
+async fn build_transaction(
+    payload: PullResponseAptos,
+    aptos_connector: &AptosConnector,
+    max_gas: u64,
+) -> Result<SignedTransaction, ConnectorError> {
+    let raw = build_raw_transaction(payload, aptos_connector, max_gas, None).await?;
+    // Estimating the gas already simulated it.
+    if aptos_connector.dry_run && aptos_connector.gas_multiplier.is_none() {
+        let account = Account::new(aptos_connector.signer.clone());
+        aptos_connector
+            .client
+            .with_aptos(|aptos| account.simulate(raw.clone(), aptos))
+            .await?;
+    }
+    aptos_connector.signer.sign(raw).await
+}
+
+/// The entry function call carrying `payload`, with the account's next sequence number.
+async fn build_raw_transaction(
+    payload: PullResponseAptos,
+    aptos_connector: &AptosConnector,
+    max_gas: u64,
+    expires_in: Option<Duration>,
+) -> Result<RawTransaction, ConnectorError> {
+    check_gas_budget(max_gas)?;
+    let account = Account::new(aptos_connector.signer.clone());
+
+    let address = AccountAddress::from_hex_literal(&aptos_connector.sc_addr)?;
 
-    let aptos_arg = TransactionPayload::EntryFunction(EntryFunction::new(
+    let entry = EntryFunction::new(
         ModuleId::new(address, Identifier::new(MODULE).unwrap()),
         Identifier::new(ENTRY).unwrap(),
         vec![],
         vec![
-            bcs::to_bytes(&AccountAddress::from_hex_literal(&payload.dkg_object).unwrap()).unwrap(),
-            bcs::to_bytes(
-                &AccountAddress::from_hex_literal(&payload.oracle_holder_object).unwrap(),
-            )
+            bcs::to_bytes(&AccountAddress::from_hex_literal(&payload.dkg_object)?).unwrap(),
+            bcs::to_bytes(&AccountAddress::from_hex_literal(
+                &payload.oracle_holder_object,
+            )?)
             .unwrap(),
             payload.vote_smr_block_round,
             payload.vote_smr_block_timestamp,
@@ -62,41 +277,120 @@ pub async fn invoke_aptos_chain(payload: PullResponseAptos, aptos_connector: Apt
             payload.sig,
             payload.pair_mask,
         ],
-    ));
+    );
+    // Verbose, but the exact arguments are what a failed call has to be reproduced with.
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        module = %entry.module(),
+        function = %entry.function(),
+        args = ?entry.args().iter().map(hex::encode).collect::<Vec<_>>(),
+        "built pull proof entry function call"
+    );
+    let aptos_arg = TransactionPayload::EntryFunction(entry);
 
     let sequence_number = aptos_connector
         .client
         .with_aptos(|aptos| account.get_seq_num(aptos))
-        .await
-        .unwrap();
-    let tx = aptos_connector
+        .await?;
+    let gas_budget = match aptos_connector.gas_multiplier {
+        None => max_gas,
+        Some(multiplier) => {
+            let gas_used = aptos_connector
+                .client
+                .with_aptos(|aptos| {
+                    account.estimate_gas(&aptos_arg, aptos, sequence_number, max_gas)
+                })
+                .await?;
+            log::debug!("aptos: simulated pull proof used {gas_used} gas units");
+            ((gas_used as f64 * multiplier).ceil() as u64).min(max_gas)
+        }
+    };
+    aptos_connector
         .client
         .with_aptos(|aptos| {
-            account.setup_transaction(
-                &aptos_arg,
-                aptos,
-                sequence_number,
-                aptos_connector.gas_budget,
-            )
+            account.raw_transaction(&aptos_arg, aptos, sequence_number, gas_budget, expires_in)
         })
         .await
-        .unwrap();
+}
 
-    let response = aptos_connector
-        .client
-        .with_aptos(|aptos| aptos.submit_and_wait(&tx))
-        .await
-        .unwrap()
-        .into_inner();
+/// Turns the fullnode's account of a committed transaction into its outcome.
+fn check_execution(
+    tx_hash: TransactionHash,
+    committed: &Transaction,
+) -> Result<ExecutionStatus, ConnectorError> {
+    let info = committed
+        .transaction_info()
+        .map_err(|e| ConnectorError::Submission(e.to_string()))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        tx_hash = %info.hash,
+        gas_used = info.gas_used.0,
+        success = info.success,
+        vm_status = %info.vm_status,
+        "pull proof executed"
+    );
+    if !info.success {
+        if let Some((location, code)) = parse_move_abort(&info.vm_status) {
+            return Err(ConnectorError::MoveAbort {
+                tx_hash,
+                location,
+                code,
+                vm_status: info.vm_status.clone(),
+            });
+        }
+        return Err(ConnectorError::Execution {
+            tx_hash,
+            vm_status: info.vm_status.clone(),
+        });
+    }
+    log::debug!("aptos: pull proof executed in transaction {tx_hash}");
+    Ok(ExecutionStatus {
+        tx_hash,
+        version: info.version.0,
+        gas_used: info.gas_used.0,
+        vm_status: info.vm_status.clone(),
+    })
+}
+
+/// Splits a `vm_status` such as `Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): ...`
+/// or `Move abort in 0x1::coin: 0x10006` into the aborting module and the abort code.
+fn parse_move_abort(vm_status: &str) -> Option<(String, u64)> {
+    let (location, rest) = vm_status.strip_prefix("Move abort in ")?.split_once(": ")?;
+    let code = match rest.split_once("(0x") {
+        Some((_, code)) => code.split(')').next()?,
+        None => rest.strip_prefix("0x")?,
+    };
+    let digits = code
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .map_or(code, |end| &code[..end]);
+    let code = u64::from_str_radix(digits, 16).ok()?;
+    Some((location.to_string(), code))
+}
 
-    println!("{:?}", response.transaction_info().unwrap().hash);
+/// Smallest gas budget accepted by [`AptosConfig`].
+pub const MIN_GAS_BUDGET: u64 = 1;
+/// Largest gas budget accepted by [`AptosConfig`], the network's `max_gas_amount` cap.
+pub const MAX_GAS_BUDGET: u64 = 2_000_000;
+
+fn check_gas_budget(gas_budget: u64) -> Result<(), ConnectorError> {
+    if !(MIN_GAS_BUDGET..=MAX_GAS_BUDGET).contains(&gas_budget) {
+        return Err(ConnectorError::GasBudgetOutOfRange {
+            gas_budget,
+            min: MIN_GAS_BUDGET,
+            max: MAX_GAS_BUDGET,
+        });
+    }
+    Ok(())
 }
 
 pub struct AptosConfig<'a> {
-    secret_key: &'a str,
-    client_url: &'a str,
-    sc_address: &'a str,
+    secret_key: Cow<'a, str>,
+    client_url: Cow<'a, str>,
+    sc_address: Cow<'a, str>,
     gas_budget: u64,
+    gas_multiplier: Option<f64>,
+    dry_run: bool,
 }
 
 impl<'a> AptosConfig<'a> {
@@ -107,24 +401,218 @@ impl<'a> AptosConfig<'a> {
         gas_budget: u64,
     ) -> Self {
         Self {
-            secret_key,
-            client_url,
-            sc_address,
+            secret_key: secret_key.into(),
+            client_url: client_url.into(),
+            sc_address: sc_address.into(),
+            gas_budget,
+            gas_multiplier: None,
+            dry_run: false,
+        }
+    }
+
+    /// Reads `APTOS_SECRET_KEY`, `APTOS_RPC_URL`, `APTOS_CONTRACT_ADDRESS` and
+    /// `APTOS_GAS_BUDGET`, then validates them. Fails with [`ConnectorError::MissingEnv`] naming
+    /// every unset variable.
+    pub fn from_env() -> Result<AptosConfig<'static>, ConnectorError> {
+        let mut env = Env::default();
+        let secret_key = env.required("APTOS_SECRET_KEY");
+        let client_url = env.required("APTOS_RPC_URL");
+        let sc_address = env.required("APTOS_CONTRACT_ADDRESS");
+        let gas_budget = env.required("APTOS_GAS_BUDGET");
+        env.finish().map_err(ConnectorError::MissingEnv)?;
+
+        let gas_budget = gas_budget
+            .parse()
+            .map_err(|_| ConnectorError::InvalidEnv("APTOS_GAS_BUDGET"))?;
+        let conf = AptosConfig {
+            secret_key: secret_key.into(),
+            client_url: client_url.into(),
+            sc_address: sc_address.into(),
             gas_budget,
+            gas_multiplier: None,
+            dry_run: false,
+        };
+        conf.validate()?;
+        Ok(conf)
+    }
+
+    /// Simulate the transaction before submitting it and use the gas it used, scaled by
+    /// `multiplier`, as the max gas amount. `gas_budget` still caps the estimate.
+    pub fn with_gas_estimate(mut self, multiplier: f64) -> Self {
+        self.gas_multiplier = Some(multiplier);
+        self
+    }
+
+    /// Build, simulate and sign every submission but never submit it, e.g. to test a deployment
+    /// script against a mainnet config. Every `invoke_aptos_chain*` function then returns the
+    /// transaction it would have submitted as [`Submission::DryRun`].
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    pub fn builder() -> AptosConfigBuilder<'a> {
+        AptosConfigBuilder::default()
+    }
+
+    /// Checks that the key, url, contract address and gas budget are well formed.
+    /// [`AptosConnector::new`] runs this as well, so a bad config never reaches
+    /// [`invoke_aptos_chain`].
+    pub fn validate(&self) -> Result<(), ConnectorError> {
+        PrivateKeySigner::from_secret_key(&self.secret_key)?;
+        self.validate_settings()
+    }
+
+    /// Everything [`AptosConfig::validate`] checks except the secret key.
+    fn validate_settings(&self) -> Result<(), ConnectorError> {
+        let url = self
+            .client_url
+            .parse::<reqwest::Url>()
+            .map_err(|_| ConnectorError::InvalidUrl)?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ConnectorError::InvalidUrl);
         }
+
+        AccountAddress::from_hex_literal(&self.sc_address)
+            .map_err(|_| ConnectorError::InvalidContractAddress)?;
+
+        check_gas_budget(self.gas_budget)?;
+
+        if let Some(multiplier) = self.gas_multiplier {
+            if !multiplier.is_finite() || multiplier < 1.0 {
+                return Err(ConnectorError::InvalidGasMultiplier(multiplier));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for AptosConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AptosConfig")
+            .field("secret_key", &"***")
+            .field("client_url", &self.client_url)
+            .field("sc_address", &self.sc_address)
+            .field("gas_budget", &self.gas_budget)
+            .field("gas_multiplier", &self.gas_multiplier)
+            .field("dry_run", &self.dry_run)
+            .finish()
+    }
+}
+
+/// Builds a validated [`AptosConfig`].
+#[derive(Default)]
+pub struct AptosConfigBuilder<'a> {
+    secret_key: Option<&'a str>,
+    client_url: Option<&'a str>,
+    sc_address: Option<&'a str>,
+    gas_budget: Option<u64>,
+    gas_multiplier: Option<f64>,
+    dry_run: bool,
+}
+
+impl fmt::Debug for AptosConfigBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AptosConfigBuilder")
+            .field("secret_key", &self.secret_key.map(|_| "***"))
+            .field("client_url", &self.client_url)
+            .field("sc_address", &self.sc_address)
+            .field("gas_budget", &self.gas_budget)
+            .field("gas_multiplier", &self.gas_multiplier)
+            .field("dry_run", &self.dry_run)
+            .finish()
+    }
+}
+
+impl<'a> AptosConfigBuilder<'a> {
+    /// Hex-encoded ed25519 secret key, with or without the `0x` prefix.
+    pub fn secret_key(mut self, secret_key: &'a str) -> Self {
+        self.secret_key = Some(secret_key);
+        self
+    }
+
+    /// Aptos REST endpoint, e.g. `https://fullnode.testnet.aptoslabs.com/v1`.
+    pub fn client_url(mut self, client_url: &'a str) -> Self {
+        self.client_url = Some(client_url);
+        self
+    }
+
+    /// Address the contract module is published under, as a `0x` hex literal.
+    pub fn sc_address(mut self, sc_address: &'a str) -> Self {
+        self.sc_address = Some(sc_address);
+        self
+    }
+
+    /// Max gas units for the submission, between [`MIN_GAS_BUDGET`] and [`MAX_GAS_BUDGET`].
+    pub fn gas_budget(mut self, gas_budget: u64) -> Self {
+        self.gas_budget = Some(gas_budget);
+        self
+    }
+
+    /// See [`AptosConfig::with_gas_estimate`].
+    pub fn gas_estimate(mut self, multiplier: f64) -> Self {
+        self.gas_multiplier = Some(multiplier);
+        self
+    }
+
+    /// See [`AptosConfig::with_dry_run`].
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn build(self) -> Result<AptosConfig<'a>, ConnectorError> {
+        let mut conf = AptosConfig::new(
+            self.secret_key
+                .ok_or(ConnectorError::MissingField("secret_key"))?,
+            self.client_url
+                .ok_or(ConnectorError::MissingField("client_url"))?,
+            self.sc_address
+                .ok_or(ConnectorError::MissingField("sc_address"))?,
+            self.gas_budget
+                .ok_or(ConnectorError::MissingField("gas_budget"))?,
+        );
+        conf.gas_multiplier = self.gas_multiplier;
+        conf.dry_run = self.dry_run;
+        conf.validate()?;
+        Ok(conf)
     }
 }
 
 #[derive(Clone)]
 pub struct AptosConnector {
-    secret_key: String,
+    signer: Arc<dyn Signer>,
     client: ClientWrapper,
     sc_addr: String,
     gas_budget: u64,
+    gas_multiplier: Option<f64>,
+    dry_run: bool,
 }
 
 impl AptosConnector {
     pub async fn new(conf: AptosConfig<'_>) -> Result<Self, ConnectorError> {
+        let signer = PrivateKeySigner::from_secret_key(&conf.secret_key)?;
+        Self::with_signer(conf, signer).await
+    }
+
+    /// A connector for the account of `public_key` that holds no key, for a host that builds
+    /// submissions with [`build_unsigned_aptos_chain`] or broadcasts them with
+    /// [`broadcast_aptos_chain`] while the key stays on another machine. The secret key in
+    /// `conf` is ignored, and anything that would sign, such as [`invoke_aptos_chain`], fails.
+    pub async fn watch_only(
+        conf: AptosConfig<'_>,
+        public_key: Ed25519PublicKey,
+    ) -> Result<Self, ConnectorError> {
+        Self::with_signer(conf, WatchOnly { public_key }).await
+    }
+
+    /// Like [`AptosConnector::new`], but signs with `signer` instead of the secret key in
+    /// `conf`, which is ignored.
+    pub async fn with_signer(
+        conf: AptosConfig<'_>,
+        signer: impl Signer + 'static,
+    ) -> Result<Self, ConnectorError> {
+        conf.validate_settings()?;
         let url = conf
             .client_url
             .parse::<reqwest::Url>()
@@ -133,9 +621,11 @@ impl AptosConnector {
 
         Ok(Self {
             client: ClientWrapper::new(client, None),
-            secret_key: conf.secret_key.to_string(),
+            signer: Arc::new(signer),
             sc_addr: conf.sc_address.to_string(),
             gas_budget: conf.gas_budget,
+            gas_multiplier: conf.gas_multiplier,
+            dry_run: conf.dry_run,
         })
     }
 }
@@ -183,21 +673,89 @@ impl ClientWrapper {
     }
 }
 
+/// Signs the connector's transactions, e.g. with a key held in a KMS or a hardware wallet.
+///
+/// [`PrivateKeySigner`] signs with an in-memory key, which is what [`AptosConnector::new`] uses.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// The key the sender address is derived from.
+    fn public_key(&self) -> Ed25519PublicKey;
+
+    /// Signs `txn`, returning it ready for submission.
+    async fn sign(&self, txn: RawTransaction) -> Result<SignedTransaction, ConnectorError>;
+}
+
+/// A [`Signer`] holding an ed25519 secret key in memory.
+pub struct PrivateKeySigner {
+    key: Ed25519PrivateKey,
+}
+
+impl PrivateKeySigner {
+    /// Parses a hex-encoded ed25519 secret key, with or without the `0x` prefix.
+    pub fn from_secret_key(input: &str) -> Result<Self, ConnectorError> {
+        let input = input.trim_start_matches("0x");
+        let h = hex::decode(input).map_err(|_| ConnectorError::InvalidSecretKey)?;
+        let signing_key =
+            SecretKey::from_bytes(&h).map_err(|_| ConnectorError::InvalidSecretKey)?;
+        let key = Ed25519PrivateKey::try_from(&*signing_key.to_bytes().to_vec())
+            .map_err(|_| ConnectorError::InvalidSecretKey)?;
+        Ok(Self { key })
+    }
+}
+
+impl fmt::Debug for PrivateKeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKeySigner")
+            .field("public_key", &self.public_key())
+            .field("key", &"***")
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Signer for PrivateKeySigner {
+    fn public_key(&self) -> Ed25519PublicKey {
+        Ed25519PublicKey::from(&self.key)
+    }
+
+    async fn sign(&self, txn: RawTransaction) -> Result<SignedTransaction, ConnectorError> {
+        txn.sign(&self.key, self.public_key())
+            .map(|txn| txn.into_inner())
+            .map_err(|e| ConnectorError::Signing(e.to_string()))
+    }
+}
+
+/// The [`Signer`] of [`AptosConnector::watch_only`].
+struct WatchOnly {
+    public_key: Ed25519PublicKey,
+}
+
+#[async_trait]
+impl Signer for WatchOnly {
+    fn public_key(&self) -> Ed25519PublicKey {
+        self.public_key.clone()
+    }
+
+    async fn sign(&self, _txn: RawTransaction) -> Result<SignedTransaction, ConnectorError> {
+        Err(ConnectorError::Signing(
+            "watch-only connector holds no key".to_string(),
+        ))
+    }
+}
+
 pub struct Account {
-    signing_key: SecretKey,
-    sender_key: Ed25519PrivateKey,
+    signer: Arc<dyn Signer>,
 }
 
 impl Account {
+    pub fn new(signer: Arc<dyn Signer>) -> Self {
+        Account { signer }
+    }
+
     pub fn from_secret_key(input: String) -> Result<Self, ConnectorError> {
-        let input = input.trim_start_matches("0x");
-        let h = hex::decode(input).unwrap();
-        let signing_key = SecretKey::from_bytes(&h).unwrap();
-        let sender_key = Ed25519PrivateKey::try_from(&*signing_key.to_bytes().to_vec()).unwrap();
-        Ok(Account {
-            signing_key,
-            sender_key,
-        })
+        Ok(Account::new(Arc::new(PrivateKeySigner::from_secret_key(
+            &input,
+        )?)))
     }
 
     pub fn address(&self) -> String {
@@ -206,7 +764,7 @@ impl Account {
 
     pub fn auth_key(&self) -> String {
         let mut sha3 = Sha3::v256();
-        sha3.update(PublicKey::from(&self.signing_key).as_bytes());
+        sha3.update(&self.signer.public_key().to_bytes());
         sha3.update(&[0u8]);
 
         let mut output = [0u8; 32];
@@ -231,14 +789,131 @@ impl Account {
         sequence_number: u64,
         gas_budget: u64,
     ) -> Result<SignedTransaction, ConnectorError> {
-        let transaction_factory = TransactionFactory::new(chain_id(rest_client).await.unwrap())
+        let raw = self
+            .raw_transaction(payload, rest_client, sequence_number, gas_budget, None)
+            .await?;
+        self.signer.sign(raw).await
+    }
+
+    /// Simulates the transaction and returns the gas units it used.
+    pub async fn estimate_gas(
+        &self,
+        payload: &TransactionPayload,
+        rest_client: &Client,
+        sequence_number: u64,
+        gas_budget: u64,
+    ) -> Result<u64, ConnectorError> {
+        let raw = self
+            .raw_transaction(payload, rest_client, sequence_number, gas_budget, None)
+            .await?;
+        self.simulate(raw, rest_client).await
+    }
+
+    /// Simulates `raw` and returns the gas units it used.
+    pub async fn simulate(
+        &self,
+        raw: RawTransaction,
+        rest_client: &Client,
+    ) -> Result<u64, ConnectorError> {
+        // The node refuses to simulate transactions that carry a valid signature.
+        let tx = SignedTransaction::new(
+            raw,
+            self.signer.public_key(),
+            Ed25519Signature::dummy_signature(),
+        );
+        let simulated = rest_client
+            .simulate(&tx)
+            .await
+            .map_err(|e| ConnectorError::Simulation(e.to_string()))?
+            .into_inner();
+        let info = &simulated
+            .first()
+            .ok_or_else(|| ConnectorError::Simulation("empty simulation result".to_string()))?
+            .info;
+        if !info.success {
+            return Err(ConnectorError::Simulation(info.vm_status.clone()));
+        }
+        Ok(info.gas_used.0)
+    }
+
+    async fn raw_transaction(
+        &self,
+        payload: &TransactionPayload,
+        rest_client: &Client,
+        sequence_number: u64,
+        gas_budget: u64,
+        expires_in: Option<Duration>,
+    ) -> Result<RawTransaction, ConnectorError> {
+        let mut transaction_factory = TransactionFactory::new(chain_id(rest_client).await?)
             .with_gas_unit_price(100)
             .with_max_gas_amount(gas_budget);
+        if let Some(expires_in) = expires_in {
+            transaction_factory =
+                transaction_factory.with_transaction_expiration_time(expires_in.as_secs());
+        }
+
+        Ok(transaction_factory
+            .payload(payload.clone())
+            .sender(self.to_address()?)
+            .sequence_number(sequence_number)
+            .build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0x5d996aa76b3212142792d9130796cd2e11e3c445a93118c08414df4f66bc60ec";
+
+    #[test]
+    fn config_debug_hides_secret_key() {
+        let conf = AptosConfig::new(
+            SECRET,
+            "https://fullnode.testnet.aptoslabs.com/v1",
+            "0x1",
+            MIN_GAS_BUDGET,
+        );
+        for debug in [format!("{conf:?}"), format!("{conf:#?}")] {
+            assert!(!debug.contains(SECRET), "{debug}");
+            assert!(debug.contains("***"), "{debug}");
+        }
+    }
+
+    #[test]
+    fn config_builder_debug_hides_secret_key() {
+        let builder = AptosConfig::builder()
+            .secret_key(SECRET)
+            .client_url("https://fullnode.testnet.aptoslabs.com/v1")
+            .sc_address("0x1");
+        for debug in [format!("{builder:?}"), format!("{builder:#?}")] {
+            assert!(!debug.contains(SECRET), "{debug}");
+            assert!(debug.contains("***"), "{debug}");
+        }
+    }
+
+    #[test]
+    fn parse_move_abort_reads_named_codes() {
+        assert_eq!(
+            parse_move_abort(
+                "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins"
+            ),
+            Some(("0x1::coin".to_string(), 0x10006))
+        );
+    }
+
+    #[test]
+    fn parse_move_abort_reads_bare_codes() {
+        assert_eq!(
+            parse_move_abort("Move abort in 0xcafe::pull_example: 0x2a"),
+            Some(("0xcafe::pull_example".to_string(), 0x2a))
+        );
+    }
 
-        let sender_key = self.sender_key.clone();
-        let sender_account =
-            &mut LocalAccount::new(self.to_address()?, sender_key, sequence_number);
-        Ok(sender_account
-            .sign_with_transaction_builder(transaction_factory.payload(payload.clone())))
+    #[test]
+    fn parse_move_abort_ignores_other_statuses() {
+        assert_eq!(parse_move_abort("Executed successfully"), None);
+        assert_eq!(parse_move_abort("Out of gas"), None);
+        assert_eq!(parse_move_abort("Move abort in 0x1::coin: unknown"), None);
     }
 }
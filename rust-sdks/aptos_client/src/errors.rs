@@ -1,14 +1,57 @@
+#[cfg(feature = "aptos")]
 use aptos::common::types::CliError;
+#[cfg(feature = "aptos")]
+use aptos_sdk::crypto::HashValue;
+#[cfg(feature = "aptos")]
 use aptos_sdk::move_types::account_address::AccountAddressParseError;
+#[cfg(feature = "aptos")]
 use thiserror::Error;
 
+pub use pull_client::errors::{ClientError, DecodeError, InvalidPairTable, UnknownChainType};
+
+#[cfg(feature = "aptos")]
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum ConnectorError {
     #[error("invalid url")]
     InvalidUrl,
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+    #[error("missing environment variables: {}", .0.join(", "))]
+    MissingEnv(Vec<&'static str>),
+    #[error("invalid value in environment variable {0}")]
+    InvalidEnv(&'static str),
+    #[error("invalid contract address")]
+    InvalidContractAddress,
+    #[error("gas budget {gas_budget} is outside {min}..={max}")]
+    GasBudgetOutOfRange { gas_budget: u64, min: u64, max: u64 },
+    #[error("gas multiplier must be a finite number of at least 1.0, got {0}")]
+    InvalidGasMultiplier(f64),
+    #[error("missing config field: {0}")]
+    MissingField(&'static str),
     #[error(transparent)]
     MoveAccountAddressParse(#[from] AccountAddressParseError),
     #[error(transparent)]
     Cli(#[from] CliError),
+    #[error("failed to sign transaction: {0}")]
+    Signing(String),
+    #[error("transaction simulation failed: {0}")]
+    Simulation(String),
+    #[error("transaction rejected on submission: {0}")]
+    Submission(String),
+    #[error("transaction {tx_hash} failed: {vm_status}")]
+    Execution {
+        tx_hash: HashValue,
+        vm_status: String,
+    },
+    /// The contract aborted. `code` is the Move abort code, `location` the aborting module.
+    #[error("transaction {tx_hash} aborted in {location} with code {code:#x}: {vm_status}")]
+    MoveAbort {
+        tx_hash: HashValue,
+        location: String,
+        code: u64,
+        vm_status: String,
+    },
+    #[error("transaction {tx_hash} was not committed in time")]
+    ConfirmationTimeout { tx_hash: HashValue },
 }
@@ -1,34 +1,21 @@
-use crate::pull_service::pull_service_client::PullServiceClient;
-use pull_service::{PullRequest, PullResponse};
+//! The Supra pull service client for Aptos: the shared [`pull_client`] with the aptos
+//! connector.
 
+#[cfg(feature = "blocking")]
+pub use pull_client::blocking;
+pub use pull_client::proxy;
+#[cfg(feature = "test-util")]
+pub use pull_client::test_util;
+pub use pull_client::{
+    builder, env, fetch_proof, oracle_client, pull_service, retry, AptosClient, Client,
+    OracleClient, PartialProof, ProofEnvelope, ProofResponse, DEFAULT_CHUNK_SIZE,
+    DEFAULT_CONCURRENCY,
+};
+
+#[cfg(feature = "aptos")]
 pub mod aptos_connector;
+pub use pull_core::aptos_proof;
+pub use pull_core::chain_type;
 pub mod errors;
-
-pub mod pull_service {
-    tonic::include_proto!("pull_service");
-}
-
-pub struct Client {
-    client: PullServiceClient<tonic::transport::Channel>,
-}
-
-impl Client {
-    pub async fn new(address: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = PullServiceClient::connect(address).await?;
-        Ok(Self { client })
-    }
-
-    pub async fn get_proof(
-        &mut self,
-        request: &PullRequest,
-    ) -> Result<PullResponse, Box<dyn std::error::Error>> {
-        match self
-            .client
-            .get_proof(tonic::Request::new(request.clone()))
-            .await
-        {
-            Ok(response) => Ok(response.into_inner()),
-            Err(status) => Err(Box::new(status)),
-        }
-    }
-}
+pub use pull_core::pairs;
+pub use pull_core::price_data;
@@ -1,4 +1,7 @@
-use evm_pull_client::ethereum_connector::invoke_eth_chain;
+use evm_pull_client::chain_type::ChainType;
+use evm_pull_client::ethereum_connector::{
+    invoke_eth_chain, EthereumConfig, EthereumConnector, GasPricing,
+};
 use evm_pull_client::pull_service::pull_response::Resp;
 use evm_pull_client::{pull_service, Client};
 
@@ -10,7 +13,8 @@ async fn main() {
     // Create a PullRequest
     let request = pull_service::PullRequest {
         pair_indexes: vec![0, 21, 61, 49], // Set the pair indexes as an array
-        chain_type: "evm".to_string(),     // Set the chain type (evm, sui, aptos)
+        chain_type: ChainType::Evm.to_string(), // Set the chain type (evm, sui, aptos)
+        round: None,                       // Set a past round to fetch, or None for the latest
     };
 
     // Call the get_proof function and handle the result
@@ -27,6 +31,17 @@ async fn main() {
 
 async fn call_contract(input: pull_service::PullResponse) {
     if let Some(Resp::Evm(evm)) = input.resp {
-        invoke_eth_chain(evm).await
+        let eth_connector = EthereumConnector::new(EthereumConfig::new(
+            "<PRIVATE KEY>",      // Your Private Key
+            "<RPC URL>",          // Rpc url for desired chain
+            "<CONTRACT ADDRESS>", // Address of your smart contract
+            GasPricing::default(),
+        ))
+        .await
+        .unwrap();
+        match invoke_eth_chain(evm, eth_connector).await {
+            Ok(submission) => println!("{submission:?}"),
+            Err(e) => eprintln!("{e}"),
+        }
     }
 }
@@ -0,0 +1,48 @@
+#[cfg(feature = "evm")]
+use alloy::primitives::{Bytes, B256};
+#[cfg(feature = "evm")]
+use alloy::rpc::types::TransactionReceipt;
+#[cfg(feature = "evm")]
+use alloy::transports::TransportError;
+pub use pull_client::errors::{ClientError, DecodeError, InvalidPairTable, UnknownChainType};
+#[cfg(feature = "evm")]
+pub use pull_core::errors::VerificationError;
+#[cfg(feature = "evm")]
+use thiserror::Error;
+
+#[cfg(feature = "evm")]
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ConnectorError {
+    #[error("invalid url")]
+    InvalidUrl,
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+    #[error("failed to decrypt keystore: {0}")]
+    Keystore(String),
+    #[error("invalid contract address")]
+    InvalidContractAddress,
+    #[error("contract address fails its EIP-55 checksum, expected {expected}")]
+    ContractAddressChecksum { expected: String },
+    #[error("missing environment variables: {}", .0.join(", "))]
+    MissingEnv(Vec<&'static str>),
+    #[error(transparent)]
+    Provider(#[from] TransportError),
+    #[error("contract call failed: {0}")]
+    Contract(String),
+    #[error("failed to sign transaction: {0}")]
+    Signing(String),
+    #[error("simulation reverted: {}", reason.as_deref().unwrap_or("no reason given"))]
+    SimulationReverted { reason: Option<String>, data: Bytes },
+    #[error("transaction {tx_hash:?} reverted")]
+    Reverted {
+        tx_hash: B256,
+        receipt: Box<TransactionReceipt>,
+    },
+    #[error("transaction {tx_hash:?} was dropped from the mempool")]
+    Dropped { tx_hash: B256 },
+    #[error("transaction {tx_hash:?} was not confirmed in time")]
+    ConfirmationTimeout { tx_hash: B256 },
+    #[error("no proofs to submit")]
+    EmptyBatch,
+}
@@ -1,50 +1,1018 @@
-use crate::pull_contract::MockOracleClient;
+use crate::env::Env;
+use crate::errors::ConnectorError;
+use crate::pull_contract::{
+    decode_batch_results, encode_batch, encode_update, UpdateCall, MULTICALL3_ADDRESS,
+};
 use crate::pull_service::PullResponseEvm;
-use ethers::core::k256::FieldBytes;
-use ethers::types::{Bytes, U256};
-use ethers::{
-    prelude::{k256::ecdsa::SigningKey, SignerMiddleware},
-    providers::{Http, Middleware, Provider},
-    signers::LocalWallet,
-    types::Address,
-    utils::secret_key_to_address,
+use alloy::eips::{BlockNumberOrTag, Encodable2718};
+use alloy::network::{Ethereum, EthereumWallet, Network, TransactionBuilder};
+use alloy::primitives::{Address, Bytes, B256};
+use alloy::providers::fillers::{FillProvider, JoinFill, WalletFiller};
+use alloy::providers::utils::JoinedRecommendedFillers;
+use alloy::providers::{
+    PendingTransactionBuilder, PendingTransactionError, Provider, ProviderBuilder, RootProvider,
 };
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use alloy::signers::Signature;
+use alloy::sol_types::{Revert, SolError};
+use alloy::transports::http::reqwest::Url;
+use alloy::transports::{RpcError, TransportError};
+use futures::future;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use zeroize::Zeroizing;
+
+pub use alloy::network::TxSigner;
+pub use alloy::signers::local::PrivateKeySigner;
+pub use alloy::signers::Signer;
+
+type WalletProvider =
+    FillProvider<JoinFill<JoinedRecommendedFillers, WalletFiller<EthereumWallet>>, RootProvider>;
+
+/// What a submission did: sent the transaction, or, with [`EthereumConfig::with_dry_run`],
+/// only signed it.
+#[derive(Clone, Debug)]
+pub enum Submission<T> {
+    Sent(T),
+    /// The signed transaction that would have been sent.
+    DryRun {
+        tx_hash: B256,
+        raw_transaction: Bytes,
+    },
+}
+
+impl<T> Submission<T> {
+    /// What was sent, `None` for a dry run.
+    pub fn sent(self) -> Option<T> {
+        match self {
+            Submission::Sent(sent) => Some(sent),
+            Submission::DryRun { .. } => None,
+        }
+    }
+}
+
+/// Sends the proof and returns the hash of its transaction without waiting for it.
+///
+/// With [`EthereumConfig::with_simulation`], fails with [`ConnectorError::SimulationReverted`]
+/// instead of sending a proof the contract would reject.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(pair_indexes = ?evm.pair_indexes))
+)]
+pub async fn invoke_eth_chain<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    evm: PullResponseEvm,
+    eth_connector: EthereumConnector<S>,
+) -> Result<Submission<B256>, ConnectorError> {
+    let tx = build_call(&evm, &eth_connector).await?;
+    if eth_connector.dry_run {
+        simulate(&tx, &eth_connector).await?;
+        return dry_run(tx, &eth_connector).await;
+    }
+    let tx = prepare(tx, &eth_connector, eth_connector.simulate).await?;
+    let tx_hash = *send_or_reset_nonce(tx, &eth_connector).await?.tx_hash();
+    #[cfg(feature = "tracing")]
+    tracing::info!(?tx_hash, "pull proof submitted");
+    Ok(Submission::Sent(tx_hash))
+}
+
+/// Submits the proof like [`invoke_eth_chain`], then waits until the transaction has the
+/// number of confirmations set by [`EthereumConfig::with_confirmations`], one by default.
+///
+/// Fails with [`ConnectorError::Reverted`] if the transaction was mined but reverted, and with
+/// [`ConnectorError::ConfirmationTimeout`] if it is not confirmed within `timeout`. A dry run
+/// returns [`Submission::DryRun`] instead of waiting.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(pair_indexes = ?evm.pair_indexes, confirmations = eth_connector.confirmations)
+    )
+)]
+pub async fn invoke_eth_chain_and_wait<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    evm: PullResponseEvm,
+    eth_connector: EthereumConnector<S>,
+    timeout: Duration,
+) -> Result<Submission<TransactionReceipt>, ConnectorError> {
+    let tx = build_call(&evm, &eth_connector).await?;
+    if eth_connector.dry_run {
+        simulate(&tx, &eth_connector).await?;
+        return dry_run(tx, &eth_connector).await;
+    }
+    let tx = prepare(tx, &eth_connector, eth_connector.simulate).await?;
+    send_and_wait(tx, &eth_connector, timeout)
+        .await
+        .map(Submission::Sent)
+}
+
+/// Submits the proof like [`invoke_eth_chain_and_wait`], but a retry with the same `key` is
+/// treated as the same submission rather than a new one.
+///
+/// The first call under `key` takes a nonce and every retry signs with that same nonce, so at
+/// most one of the attempts can land. If an earlier attempt already has a receipt it is
+/// returned without sending anything. Retries may use fresh gas prices, which the node may
+/// reject while an earlier attempt is still pending; retry again later in that case.
+///
+/// Calls with the same key run one at a time up to their send, so concurrent ones share the
+/// nonce too. If no attempt reached the node yet, a failed send gives the nonce back.
+///
+/// The key is forgotten once a receipt is returned, successful or reverted, or after
+/// [`EthereumConnector::forget_submission`]. Keys are shared by clones of the connector.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(pair_indexes = ?evm.pair_indexes, key = %key, confirmations = eth_connector.confirmations)
+    )
+)]
+pub async fn invoke_eth_chain_idempotent<
+    S: Signer + TxSigner<Signature> + Send + Sync + 'static,
+>(
+    evm: PullResponseEvm,
+    eth_connector: EthereumConnector<S>,
+    key: &str,
+    timeout: Duration,
+) -> Result<Submission<TransactionReceipt>, ConnectorError> {
+    let mut tx = build_call(&evm, &eth_connector).await?;
+    if eth_connector.dry_run {
+        simulate(&tx, &eth_connector).await?;
+        return dry_run(tx, &eth_connector).await;
+    }
+
+    // Reserved under one guard, so concurrent calls with the same key share one entry, and
+    // each waits on the entry until the one before it has recorded its send.
+    let entry = eth_connector
+        .submissions
+        .lock()
+        .await
+        .entry(key.to_owned())
+        .or_default()
+        .clone();
+    let mut submission = entry.lock().await;
+    for tx_hash in &submission.tx_hashes {
+        if let Some(receipt) = eth_connector
+            .provider
+            .get_transaction_receipt(*tx_hash)
+            .await?
+        {
+            log::debug!("evm: submission {key} already landed in {tx_hash:?}");
+            drop(submission);
+            eth_connector.forget_submission(key).await;
+            return check_receipt(receipt).map(Submission::Sent);
+        }
+    }
+    let nonce = match submission.nonce {
+        Some(nonce) => {
+            log::debug!("evm: retrying submission {key} with nonce {nonce}");
+            nonce
+        }
+        None => {
+            if eth_connector.simulate {
+                simulate(&tx, &eth_connector).await?;
+                log::debug!("evm: pull proof simulation succeeded");
+            }
+            let nonce = match eth_connector.next_nonce().await? {
+                Some(nonce) => nonce,
+                None => eth_connector.pending_nonce().await?,
+            };
+            submission.nonce = Some(nonce);
+            nonce
+        }
+    };
+    tx.set_nonce(nonce);
+
+    let pending = match send(tx, &eth_connector).await {
+        Ok(pending) => pending,
+        Err(e) => {
+            // No attempt under the key reached the node, so its nonce is free again.
+            if submission.tx_hashes.is_empty() {
+                submission.nonce = None;
+                eth_connector.reset_nonce().await;
+            }
+            return Err(e);
+        }
+    };
+    submission.tx_hashes.push(*pending.tx_hash());
+    drop(submission);
+    let result = confirm(pending, &eth_connector, timeout).await;
+    if matches!(result, Ok(_) | Err(ConnectorError::Reverted { .. })) {
+        eth_connector.forget_submission(key).await;
+    }
+    result.map(Submission::Sent)
+}
+
+/// One chain [`invoke_eth_chains_and_wait`] submits to.
+pub struct ChainTarget<'a, S = PrivateKeySigner> {
+    /// The chain's RPC URL and pull contract. Its secret key is ignored in favour of `signer`.
+    pub conf: EthereumConfig<'a>,
+    pub signer: S,
+}
+
+impl<'a, S> ChainTarget<'a, S> {
+    /// A target with the default config, see [`EthereumConfig::new`]. Replace `conf` for
+    /// other gas pricing, confirmations and so on.
+    pub fn new(rpc_url: &'a str, contract_address: &'a str, signer: S) -> Self {
+        Self {
+            conf: EthereumConfig::new("", rpc_url, contract_address, GasPricing::default()),
+            signer,
+        }
+    }
+}
+
+/// Submits the same proof to every one of `targets` concurrently, each like
+/// [`invoke_eth_chain_and_wait`], e.g. to keep a feed current on several rollups.
+///
+/// Returns one result per target, in the order given. A chain that fails, while connecting
+/// or submitting, does not affect the others. `timeout` applies to each chain separately.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(pair_indexes = ?evm.pair_indexes, chains = targets.len())
+    )
+)]
+pub async fn invoke_eth_chains_and_wait<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    evm: &PullResponseEvm,
+    targets: Vec<ChainTarget<'_, S>>,
+    timeout: Duration,
+) -> Vec<Result<Submission<TransactionReceipt>, ConnectorError>> {
+    let submissions = targets.into_iter().map(|target| async move {
+        let eth_connector = EthereumConnector::with_signer(target.conf, target.signer).await?;
+        invoke_eth_chain_and_wait(evm.clone(), eth_connector, timeout).await
+    });
+    future::join_all(submissions).await
+}
+
+/// What [`invoke_eth_chain_batch_and_wait`] submitted.
+#[derive(Clone, Debug)]
+pub struct BatchReceipt {
+    pub tx_hash: B256,
+    pub receipt: TransactionReceipt,
+    /// Whether each proof's update succeeded, in the order the proofs were given. Multicall3
+    /// only reports this to an `eth_call`, so it comes from simulating the batch just before it
+    /// was sent.
+    pub succeeded: Vec<bool>,
+}
+
+/// Submits all of `evms` in one transaction through Multicall3's `aggregate3` (see
+/// [`EthereumConfig::with_multicall_address`]), then waits for it like
+/// [`invoke_eth_chain_and_wait`].
+///
+/// Every update may fail without reverting the others, so one stale proof does not sink the
+/// batch. The batch is always simulated first, and nothing is sent if every update in it would
+/// fail: the first failure is returned as [`ConnectorError::SimulationReverted`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(proofs = evms.len(), confirmations = eth_connector.confirmations)
+    )
+)]
+pub async fn invoke_eth_chain_batch_and_wait<
+    S: Signer + TxSigner<Signature> + Send + Sync + 'static,
+>(
+    evms: &[PullResponseEvm],
+    eth_connector: EthereumConnector<S>,
+    timeout: Duration,
+) -> Result<Submission<BatchReceipt>, ConnectorError> {
+    if evms.is_empty() {
+        return Err(ConnectorError::EmptyBatch);
+    }
+    let updates: Vec<UpdateCall> = evms
+        .iter()
+        .map(|evm| encode_update(evm, eth_connector.sc_addr))
+        .collect();
+    let tx = build_tx(
+        encode_batch(&updates, eth_connector.multicall),
+        &eth_connector,
+    )
+    .await?;
+
+    let output = eth_connector
+        .provider
+        .call(tx.clone())
+        .await
+        .map_err(simulation_error)?;
+    let results = decode_batch_results(&output)
+        .map_err(|e| ConnectorError::Contract(format!("invalid multicall result: {e}")))?;
+    if results.len() != evms.len() {
+        return Err(ConnectorError::Contract(format!(
+            "multicall returned {} results for {} calls",
+            results.len(),
+            evms.len()
+        )));
+    }
+    if results.iter().all(|(success, _)| !success) {
+        let (_, data) = &results[0];
+        return Err(ConnectorError::SimulationReverted {
+            reason: Revert::abi_decode(data).ok().map(|revert| revert.reason),
+            data: data.clone(),
+        });
+    }
+    let succeeded: Vec<bool> = results.into_iter().map(|(success, _)| success).collect();
+    log::debug!("evm: batch simulation succeeded for {succeeded:?}");
+
+    if eth_connector.dry_run {
+        return dry_run(tx, &eth_connector).await;
+    }
+    let tx = prepare(tx, &eth_connector, false).await?;
+    let receipt = send_and_wait(tx, &eth_connector, timeout).await?;
+    Ok(Submission::Sent(BatchReceipt {
+        tx_hash: receipt.transaction_hash,
+        receipt,
+        succeeded,
+    }))
+}
+
+/// Signs `tx` without sending it and returns it as [`Submission::DryRun`].
+async fn dry_run<T, S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    tx: TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<Submission<T>, ConnectorError> {
+    let (tx_hash, raw_transaction) = sign_only(tx, eth_connector).await?;
+    log::info!("evm: dry run, not sending transaction {tx_hash:?}");
+    Ok(Submission::DryRun {
+        tx_hash,
+        raw_transaction,
+    })
+}
+
+async fn send_and_wait<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    tx: TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+    timeout: Duration,
+) -> Result<TransactionReceipt, ConnectorError> {
+    let pending = send_or_reset_nonce(tx, eth_connector).await?;
+    confirm(pending, eth_connector, timeout).await
+}
+
+/// Sends `tx` with a nonce taken by [`prepare`], giving the nonce back if the node refused it.
+async fn send_or_reset_nonce<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    tx: TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<PendingTransactionBuilder<Ethereum>, ConnectorError> {
+    match send(tx, eth_connector).await {
+        Ok(pending) => Ok(pending),
+        Err(e) => {
+            // The node never accepted the nonce, so the local counter is ahead of it now.
+            eth_connector.reset_nonce().await;
+            Err(e)
+        }
+    }
+}
+
+async fn send<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    tx: TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<PendingTransactionBuilder<Ethereum>, ConnectorError> {
+    let pending = eth_connector
+        .provider
+        .send_transaction(tx)
+        .await
+        .map_err(|e| ConnectorError::Contract(e.to_string()))?;
+    log::debug!(
+        "evm: pull proof submitted in transaction {:?}",
+        pending.tx_hash()
+    );
+    Ok(pending)
+}
+
+async fn confirm<S>(
+    pending: PendingTransactionBuilder<Ethereum>,
+    eth_connector: &EthereumConnector<S>,
+    timeout: Duration,
+) -> Result<TransactionReceipt, ConnectorError> {
+    let tx_hash = *pending.tx_hash();
+    let receipt = tokio::time::timeout(
+        timeout,
+        pending
+            .with_required_confirmations(eth_connector.confirmations)
+            .get_receipt(),
+    )
+    .await
+    .map_err(|_| ConnectorError::ConfirmationTimeout { tx_hash })?
+    .map_err(|e| match e {
+        // Mined, then gone by the time the receipt was fetched.
+        PendingTransactionError::TransportError(RpcError::NullResp) => {
+            ConnectorError::Dropped { tx_hash }
+        }
+        PendingTransactionError::TransportError(e) => ConnectorError::Provider(e),
+        e => ConnectorError::Contract(e.to_string()),
+    })?;
+    check_receipt(receipt)
+}
+
+fn check_receipt(receipt: TransactionReceipt) -> Result<TransactionReceipt, ConnectorError> {
+    let tx_hash = receipt.transaction_hash;
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        ?tx_hash,
+        gas_used = receipt.gas_used,
+        status = receipt.status(),
+        "pull proof confirmed"
+    );
+
+    if !receipt.status() {
+        return Err(ConnectorError::Reverted {
+            tx_hash,
+            receipt: Box::new(receipt),
+        });
+    }
+    log::debug!(
+        "evm: pull proof confirmed in block {:?}, gas used {:?}",
+        receipt.block_number,
+        receipt.gas_used
+    );
+    Ok(receipt)
+}
+
+/// Runs the submission as an `eth_call` against the latest block, without broadcasting it.
+///
+/// Fails with [`ConnectorError::SimulationReverted`], carrying the revert reason when the
+/// contract gave one, if the transaction would revert.
+pub async fn simulate_eth_chain<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    evm: &PullResponseEvm,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<(), ConnectorError> {
+    simulate(&build_call(evm, eth_connector).await?, eth_connector).await
+}
+
+/// A submission with its nonce, gas, fees and chain id filled in, waiting to be signed.
+pub type UnsignedTx = <Ethereum as Network>::UnsignedTx;
+
+/// Builds the submission of `evm` without signing it, for a key held on a machine without
+/// network access.
+///
+/// The nonce is the sender's pending nonce, or the next local one with
+/// [`EthereumConfig::with_local_nonce`], and gas and fees are set as if the proof were submitted
+/// now. Sign the result with [`sign_unsigned_tx`] and send it with [`broadcast_eth_chain`].
+pub async fn build_unsigned_eth_chain<S>(
+    evm: &PullResponseEvm,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<UnsignedTx, ConnectorError> {
+    let mut tx = build_call(evm, eth_connector).await?;
+    if eth_connector.simulate {
+        simulate(&tx, eth_connector).await?;
+    }
+    let nonce = match eth_connector.next_nonce().await? {
+        Some(nonce) => nonce,
+        None => eth_connector.pending_nonce().await?,
+    };
+    tx.set_nonce(nonce);
+    tx.set_chain_id(eth_connector.chain_id);
+    tx.set_gas_limit(eth_connector.provider.estimate_gas(tx.clone()).await?);
+    if eth_connector.gas_pricing == GasPricing::Eip1559Estimated {
+        let fees = eth_connector.provider.estimate_eip1559_fees().await?;
+        tx.set_max_fee_per_gas(fees.max_fee_per_gas);
+        tx.set_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+    }
+    tx.build_unsigned()
+        .map_err(|e| ConnectorError::Contract(e.to_string()))
+}
+
+/// Signs a transaction from [`build_unsigned_eth_chain`] without touching the network and
+/// returns it encoded for [`broadcast_eth_chain`].
+pub async fn sign_unsigned_tx(
+    mut tx: UnsignedTx,
+    signer: &impl TxSigner<Signature>,
+) -> Result<Bytes, ConnectorError> {
+    let signature = signer
+        .sign_transaction(&mut tx)
+        .await
+        .map_err(|e| ConnectorError::Signing(e.to_string()))?;
+    Ok(tx.into_envelope(signature).encoded_2718().into())
+}
+
+/// Sends a transaction signed with [`sign_unsigned_tx`], or by any other means, then waits for
+/// it like [`invoke_eth_chain_and_wait`]. The connector's own signer is not used, so a
+/// [watch-only](EthereumConnector::watch_only) one will do.
+pub async fn broadcast_eth_chain<S>(
+    raw_transaction: &[u8],
+    eth_connector: &EthereumConnector<S>,
+    timeout: Duration,
+) -> Result<TransactionReceipt, ConnectorError> {
+    let pending = eth_connector
+        .provider
+        .send_raw_transaction(raw_transaction)
+        .await
+        .map_err(|e| ConnectorError::Contract(e.to_string()))?;
+    log::debug!(
+        "evm: signed pull proof broadcast in transaction {:?}",
+        pending.tx_hash()
+    );
+    confirm(pending, eth_connector, timeout).await
+}
+
+async fn simulate<S>(
+    tx: &TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<(), ConnectorError> {
+    eth_connector
+        .provider
+        .call(tx.clone())
+        .await
+        .map_err(simulation_error)?;
+    Ok(())
+}
+
+fn simulation_error(e: TransportError) -> ConnectorError {
+    match e.as_error_resp().and_then(|resp| resp.as_revert_data()) {
+        Some(data) => ConnectorError::SimulationReverted {
+            reason: Revert::abi_decode(&data).ok().map(|revert| revert.reason),
+            data,
+        },
+        None => ConnectorError::Contract(e.to_string()),
+    }
+}
 
-pub async fn invoke_eth_chain(evm: PullResponseEvm) {
-    let rpc_url = "<RPC URL>"; // Rpc url for desired chain
-    let secret_key = "<PRIVATE KEY>"; // Your Private Key
-    let contract_address = "<CONTRACT ADDRESS>"; //Address of your smart contract
+/// Signs `tx`, with the fees and nonce the node suggests, and returns its hash and raw bytes.
+/// The local nonce counter is left alone.
+async fn sign_only<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    tx: TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<(B256, Bytes), ConnectorError> {
+    let signed = eth_connector
+        .provider
+        .fill(tx)
+        .await?
+        .try_into_envelope()
+        .map_err(|e| ConnectorError::Contract(e.to_string()))?;
+    Ok((*signed.tx_hash(), signed.encoded_2718().into()))
+}
+
+async fn prepare<S: Signer + TxSigner<Signature> + Send + Sync + 'static>(
+    mut tx: TransactionRequest,
+    eth_connector: &EthereumConnector<S>,
+    simulate_first: bool,
+) -> Result<TransactionRequest, ConnectorError> {
+    // Simulate before taking a nonce, so a reverting proof doesn't leave a gap behind.
+    if simulate_first {
+        simulate(&tx, eth_connector).await?;
+        log::debug!("evm: pull proof simulation succeeded");
+    }
+    if let Some(nonce) = eth_connector.next_nonce().await? {
+        tx.set_nonce(nonce);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(contract = ?eth_connector.sc_addr, gas = ?tx.gas, nonce = ?tx.nonce, "submitting pull proof");
+    Ok(tx)
+}
+
+async fn detect_gas_pricing(provider: &impl Provider) -> Result<GasPricing, ConnectorError> {
+    let latest = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?;
+    let gas_pricing = match latest.and_then(|block| block.header.base_fee_per_gas) {
+        Some(_) => GasPricing::Eip1559Estimated,
+        None => GasPricing::Legacy,
+    };
+    log::debug!("evm: node reports {gas_pricing:?} gas pricing");
+    Ok(gas_pricing)
+}
+
+async fn build_call<S>(
+    evm: &PullResponseEvm,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<TransactionRequest, ConnectorError> {
+    build_tx(encode_update(evm, eth_connector.sc_addr), eth_connector).await
+}
+
+async fn build_tx<S>(
+    update: UpdateCall,
+    eth_connector: &EthereumConnector<S>,
+) -> Result<TransactionRequest, ConnectorError> {
+    // Verbose, but the exact calldata is what a revert has to be reproduced with.
+    #[cfg(feature = "tracing")]
+    tracing::debug!(to = ?update.to, calldata = %update.data, "built pull proof call");
+    let mut tx = TransactionRequest::default()
+        .with_from(eth_connector.from)
+        .with_to(update.to)
+        .with_input(update.data);
+    match eth_connector.gas_pricing {
+        // A gas price makes the provider's gas filler build a type 0 transaction.
+        GasPricing::Legacy => tx.set_gas_price(eth_connector.provider.get_gas_price().await?),
+        GasPricing::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => {
+            tx.set_max_fee_per_gas(max_fee_per_gas);
+            tx.set_max_priority_fee_per_gas(max_priority_fee_per_gas);
+        }
+        // Left unset, the provider fills both fees from the node's fee history. `Auto` is
+        // resolved when the connector is built, so it never gets here.
+        GasPricing::Eip1559Estimated | GasPricing::Auto => {}
+    }
+    Ok(tx)
+}
+
+/// How the submission transaction is priced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GasPricing {
+    /// Type 0 transaction using the node's `eth_gasPrice`.
+    Legacy,
+    /// Type 2 transaction with explicit fees, in wei.
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+    /// Type 2 transaction with fees estimated from the node's recent fee history.
+    Eip1559Estimated,
+    /// [`GasPricing::Eip1559Estimated`] if the node's latest block has a base fee, otherwise
+    /// [`GasPricing::Legacy`]. Checked once, when the connector is built.
+    #[default]
+    Auto,
+}
+
+pub struct EthereumConfig<'a> {
+    secret_key: Cow<'a, str>,
+    rpc_url: Cow<'a, str>,
+    sc_address: Cow<'a, str>,
+    gas_pricing: GasPricing,
+    local_nonce: bool,
+    simulate: bool,
+    confirmations: u64,
+    dry_run: bool,
+    multicall_address: Option<Cow<'a, str>>,
+}
+
+impl<'a> EthereumConfig<'a> {
+    pub fn new(
+        secret_key: &'a str,
+        rpc_url: &'a str,
+        sc_address: &'a str,
+        gas_pricing: GasPricing,
+    ) -> Self {
+        Self {
+            secret_key: secret_key.into(),
+            rpc_url: rpc_url.into(),
+            sc_address: sc_address.into(),
+            gas_pricing,
+            local_nonce: false,
+            simulate: false,
+            confirmations: 1,
+            dry_run: false,
+            multicall_address: None,
+        }
+    }
 
-    let http_provider = Provider::<Http>::try_from(rpc_url)
-        .map_err(|_| eprint!("Invalid rpc url"))
-        .unwrap();
+    /// Reads `EVM_SECRET_KEY`, `EVM_RPC_URL` and `EVM_CONTRACT_ADDRESS`, with the default gas
+    /// pricing. Fails with [`ConnectorError::MissingEnv`] naming every unset variable.
+    pub fn from_env() -> Result<EthereumConfig<'static>, ConnectorError> {
+        let mut env = Env::default();
+        let secret_key = env.required("EVM_SECRET_KEY");
+        let rpc_url = env.required("EVM_RPC_URL");
+        let sc_address = env.required("EVM_CONTRACT_ADDRESS");
+        env.finish().map_err(ConnectorError::MissingEnv)?;
 
-    let network_chain_id = http_provider.get_chainid().await.unwrap().as_u64();
+        Ok(EthereumConfig {
+            secret_key: secret_key.into(),
+            rpc_url: rpc_url.into(),
+            sc_address: sc_address.into(),
+            gas_pricing: GasPricing::default(),
+            local_nonce: false,
+            simulate: false,
+            confirmations: 1,
+            dry_run: false,
+            multicall_address: None,
+        })
+    }
 
-    let secret_key_bytes = hex::decode(secret_key)
-        .map_err(|_| eprint!("Invalid rpc url"))
-        .unwrap();
+    /// Track the signer's nonce locally instead of asking the node before every send, so several
+    /// proofs can be submitted back-to-back. Clones of the connector share the counter.
+    pub fn with_local_nonce(mut self) -> Self {
+        self.local_nonce = true;
+        self
+    }
 
-    let signer_key = SigningKey::from_bytes(FieldBytes::from_slice(secret_key_bytes.as_slice()))
-        .map_err(|_| eprint!("Invalid secret key"))
-        .unwrap();
+    /// Simulate every submission with `eth_call` first and fail with
+    /// [`ConnectorError::SimulationReverted`] instead of sending a transaction that would revert.
+    pub fn with_simulation(mut self) -> Self {
+        self.simulate = true;
+        self
+    }
 
-    let addr = secret_key_to_address(&signer_key);
+    /// Blocks [`invoke_eth_chain_and_wait`] waits for, counting the one the transaction is
+    /// mined in. Raise it on chains that reorg often; the default of 1 returns on the receipt.
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations.max(1);
+        self
+    }
 
-    let wallet = LocalWallet::new_with_signer(signer_key, addr, network_chain_id);
+    /// Build, simulate and sign every submission but never broadcast it, e.g. to test a
+    /// deployment script against a mainnet config. Every `invoke_eth_chain*` function then
+    /// returns the transaction it would have sent as [`Submission::DryRun`].
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Multicall3 deployment [`invoke_eth_chain_batch_and_wait`] sends batches through, for
+    /// chains without one at the usual [`MULTICALL3_ADDRESS`].
+    pub fn with_multicall_address(mut self, multicall_address: &'a str) -> Self {
+        self.multicall_address = Some(multicall_address.into());
+        self
+    }
+
+    /// Checks that the RPC URL parses and the contract address is 20 bytes of hex, with or
+    /// without `0x`, and passes its EIP-55 checksum if it is mixed-case. The connector
+    /// constructors check the same, so this only reports a bad config earlier.
+    pub fn validate(&self) -> Result<(), ConnectorError> {
+        parse_rpc_url(&self.rpc_url)?;
+        parse_contract_address(&self.sc_address)?;
+        if let Some(multicall_address) = &self.multicall_address {
+            parse_contract_address(multicall_address)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for EthereumConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EthereumConfig")
+            .field("secret_key", &"***")
+            .field("rpc_url", &self.rpc_url)
+            .field("sc_address", &self.sc_address)
+            .field("gas_pricing", &self.gas_pricing)
+            .field("local_nonce", &self.local_nonce)
+            .field("simulate", &self.simulate)
+            .field("confirmations", &self.confirmations)
+            .field("dry_run", &self.dry_run)
+            .field("multicall_address", &self.multicall_address)
+            .finish()
+    }
+}
+
+fn parse_rpc_url(input: &str) -> Result<Url, ConnectorError> {
+    Url::parse(input).map_err(|_| ConnectorError::InvalidUrl)
+}
+
+fn parse_contract_address(input: &str) -> Result<Address, ConnectorError> {
+    let digits = input.strip_prefix("0x").unwrap_or(input);
+    if digits.len() != 40 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ConnectorError::InvalidContractAddress);
+    }
+    let address = Address::from_str(digits).map_err(|_| ConnectorError::InvalidContractAddress)?;
+    let mixed_case = digits.bytes().any(|b| b.is_ascii_uppercase())
+        && digits.bytes().any(|b| b.is_ascii_lowercase());
+    if mixed_case {
+        let expected = address.to_checksum(None);
+        if expected[2..] != *digits {
+            return Err(ConnectorError::ContractAddressChecksum { expected });
+        }
+    }
+    Ok(address)
+}
+
+/// The nonce taken by an [`invoke_eth_chain_idempotent`] key, and every transaction sent with it.
+#[derive(Debug, Default)]
+struct PendingSubmission {
+    nonce: Option<u64>,
+    tx_hashes: Vec<B256>,
+}
+
+/// Submits pull proofs to the oracle contract.
+///
+/// Transactions are signed by `S`, any `alloy` [`Signer`] that can also sign transactions (a
+/// hardware wallet, a remote KMS, ...). [`EthereumConnector::new`] uses a [`PrivateKeySigner`]
+/// built from the configured secret key.
+#[derive(Clone)]
+pub struct EthereumConnector<S = PrivateKeySigner> {
+    provider: Arc<WalletProvider>,
+    from: Address,
+    chain_id: u64,
+    sc_addr: Address,
+    multicall: Address,
+    gas_pricing: GasPricing,
+    nonce: Option<Arc<Mutex<Option<u64>>>>,
+    simulate: bool,
+    confirmations: u64,
+    dry_run: bool,
+    submissions: Arc<Mutex<HashMap<String, Arc<Mutex<PendingSubmission>>>>>,
+    // The signer itself is owned by the provider's wallet.
+    signer: PhantomData<S>,
+}
 
-    let signed_prov = SignerMiddleware::new(http_provider, wallet);
+impl<S> fmt::Debug for EthereumConnector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EthereumConnector")
+            .field("from", &self.from)
+            .field("chain_id", &self.chain_id)
+            .field("sc_addr", &self.sc_addr)
+            .field("multicall", &self.multicall)
+            .field("gas_pricing", &self.gas_pricing)
+            .field("local_nonce", &self.nonce.is_some())
+            .field("simulate", &self.simulate)
+            .field("confirmations", &self.confirmations)
+            .field("dry_run", &self.dry_run)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EthereumConnector {
+    pub async fn new(conf: EthereumConfig<'_>) -> Result<Self, ConnectorError> {
+        let secret_key_bytes = Zeroizing::new(
+            hex::decode(conf.secret_key.trim_start_matches("0x"))
+                .map_err(|_| ConnectorError::InvalidSecretKey)?,
+        );
+        let wallet = wallet_from_bytes(&secret_key_bytes)?;
+
+        Self::with_signer(conf, wallet).await
+    }
+
+    /// Like [`EthereumConnector::new`], but decrypts the signing key from an encrypted JSON
+    /// keystore (as written by geth or `cast wallet`) instead of the secret key in `conf`, which
+    /// is ignored.
+    pub async fn from_keystore(
+        conf: EthereumConfig<'_>,
+        path: impl AsRef<Path>,
+        passphrase: impl AsRef<[u8]>,
+    ) -> Result<Self, ConnectorError> {
+        let wallet = signer_from_keystore(path, passphrase)?;
+        Self::with_signer(conf, wallet).await
+    }
+
+    /// A connector for `from` that holds no key, for a host that builds submissions with
+    /// [`build_unsigned_eth_chain`] or broadcasts them with [`broadcast_eth_chain`] while the key
+    /// stays on another machine. The secret key in `conf` is ignored, and anything that would
+    /// sign, such as [`invoke_eth_chain_and_wait`], fails.
+    pub async fn watch_only(
+        conf: EthereumConfig<'_>,
+        from: Address,
+    ) -> Result<Self, ConnectorError> {
+        let node = Node::connect(&conf).await?;
+        Self::with_wallet(conf, node, EthereumWallet::default(), from).await
+    }
+}
+
+impl<S> EthereumConnector<S> {
+    async fn with_wallet(
+        conf: EthereumConfig<'_>,
+        node: Node,
+        wallet: EthereumWallet,
+        from: Address,
+    ) -> Result<Self, ConnectorError> {
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .wallet(wallet)
+                .connect_provider(node.provider),
+        );
+
+        let gas_pricing = match conf.gas_pricing {
+            GasPricing::Auto => detect_gas_pricing(provider.root()).await?,
+            gas_pricing => gas_pricing,
+        };
+
+        Ok(Self {
+            provider,
+            from,
+            chain_id: node.chain_id,
+            sc_addr: node.sc_addr,
+            multicall: node.multicall,
+            gas_pricing,
+            nonce: conf.local_nonce.then(Default::default),
+            simulate: conf.simulate,
+            confirmations: conf.confirmations,
+            dry_run: conf.dry_run,
+            submissions: Default::default(),
+            signer: PhantomData,
+        })
+    }
+
+    async fn next_nonce(&self) -> Result<Option<u64>, ConnectorError> {
+        let Some(nonce) = &self.nonce else {
+            return Ok(None);
+        };
+        let mut nonce = nonce.lock().await;
+        let next = match *nonce {
+            Some(next) => next,
+            None => self.pending_nonce().await?,
+        };
+        *nonce = Some(next + 1);
+        Ok(Some(next))
+    }
+
+    async fn pending_nonce(&self) -> Result<u64, ConnectorError> {
+        let nonce = self
+            .provider
+            .get_transaction_count(self.from)
+            .pending()
+            .await?;
+        Ok(nonce)
+    }
+}
+
+impl<S: Signer + TxSigner<Signature> + Send + Sync + 'static> EthereumConnector<S> {
+    /// Like [`EthereumConnector::new`], but signs with `signer` instead of the secret key in
+    /// `conf`, which is ignored. The signer's chain id is set to the one reported by the node.
+    pub async fn with_signer(conf: EthereumConfig<'_>, signer: S) -> Result<Self, ConnectorError> {
+        let node = Node::connect(&conf).await?;
+        let signer = signer.with_chain_id(Some(node.chain_id));
+        let from = TxSigner::address(&signer);
+        Self::with_wallet(conf, node, EthereumWallet::new(signer), from).await
+    }
+
+    /// How submissions are priced, with [`GasPricing::Auto`] resolved to the detected type.
+    pub fn gas_pricing(&self) -> GasPricing {
+        self.gas_pricing
+    }
+
+    /// Re-reads the signer's pending nonce from the node. Call this after a send failed with a
+    /// nonce error.
+    pub async fn resync_nonce(&self) -> Result<(), ConnectorError> {
+        if let Some(nonce) = &self.nonce {
+            let mut nonce = nonce.lock().await;
+            *nonce = Some(self.pending_nonce().await?);
+        }
+        Ok(())
+    }
+
+    /// Forgets the local nonce so the next send reads it from the node again, e.g. after a
+    /// transaction was dropped from the mempool. Waits for a concurrent send to finish taking
+    /// its nonce.
+    pub async fn reset_nonce(&self) {
+        if let Some(nonce) = &self.nonce {
+            *nonce.lock().await = None;
+        }
+    }
+
+    /// Drops the nonce held for an [`invoke_eth_chain_idempotent`] key, e.g. when giving up on
+    /// a submission. A later call with the same key starts a new submission.
+    pub async fn forget_submission(&self, key: &str) {
+        self.submissions.lock().await.remove(key);
+    }
+}
+
+/// The RPC endpoint and contract addresses of a config, checked against the node.
+struct Node {
+    provider: RootProvider,
+    chain_id: u64,
+    sc_addr: Address,
+    multicall: Address,
+}
+
+impl Node {
+    async fn connect(conf: &EthereumConfig<'_>) -> Result<Self, ConnectorError> {
+        let provider = RootProvider::new_http(parse_rpc_url(&conf.rpc_url)?);
+        let sc_addr = parse_contract_address(&conf.sc_address)?;
+        let multicall = match &conf.multicall_address {
+            Some(multicall_address) => parse_contract_address(multicall_address)?,
+            None => MULTICALL3_ADDRESS,
+        };
+        let chain_id = provider.get_chain_id().await?;
+        Ok(Self {
+            provider,
+            chain_id,
+            sc_addr,
+            multicall,
+        })
+    }
+}
+
+/// Decrypts a [`PrivateKeySigner`] from an encrypted JSON keystore file. The secret key is wiped
+/// from memory when the signer is dropped.
+pub fn signer_from_keystore(
+    path: impl AsRef<Path>,
+    passphrase: impl AsRef<[u8]>,
+) -> Result<PrivateKeySigner, ConnectorError> {
+    let secret_key_bytes = Zeroizing::new(
+        eth_keystore::decrypt_key(path, passphrase)
+            .map_err(|e| ConnectorError::Keystore(e.to_string()))?,
+    );
+    wallet_from_bytes(&secret_key_bytes)
+}
+
+fn wallet_from_bytes(secret_key_bytes: &[u8]) -> Result<PrivateKeySigner, ConnectorError> {
+    if secret_key_bytes.len() != 32 {
+        return Err(ConnectorError::InvalidSecretKey);
+    }
+    // The signer's `SigningKey` zeroizes itself on drop. Its chain id is filled in from the
+    // node by `with_signer`.
+    PrivateKeySigner::from_slice(secret_key_bytes).map_err(|_| ConnectorError::InvalidSecretKey)
+}
 
-    let provider = Arc::new(signed_prov);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let sc_addr = Address::from_str(contract_address)
-        .map_err(|_| eprint!("Invalid contract address"))
-        .unwrap();
+    const SECRET: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
 
-    let sc = MockOracleClient::new(sc_addr, provider.clone());
-    let mut call = sc.get_pair_price(Bytes::from(evm.proof_bytes), U256::from(0));
-    call = call.legacy();
-    call.send().await.unwrap();
+    #[test]
+    fn config_debug_hides_secret_key() {
+        let conf = EthereumConfig::new(
+            SECRET,
+            "http://localhost:8545",
+            "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+            GasPricing::Legacy,
+        );
+        for debug in [format!("{conf:?}"), format!("{conf:#?}")] {
+            assert!(!debug.contains(SECRET), "{debug}");
+            assert!(debug.contains("***"), "{debug}");
+        }
+    }
 }
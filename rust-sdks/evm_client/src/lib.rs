@@ -1,34 +1,22 @@
-use crate::pull_service::pull_service_client::PullServiceClient;
-use pull_service::{PullRequest, PullResponse};
+//! The Supra pull service client for EVM chains: the shared [`pull_client`] with the ethereum
+//! connector and the pull contract encoding.
 
-pub mod ethereum_connector;
-mod pull_contract;
-
-pub mod pull_service {
-    tonic::include_proto!("pull_service");
-}
-
-pub struct Client {
-    client: PullServiceClient<tonic::transport::Channel>,
-}
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use pull_client::blocking;
+#[cfg(not(target_arch = "wasm32"))]
+pub use pull_client::proxy;
+#[cfg(all(feature = "test-util", not(target_arch = "wasm32")))]
+pub use pull_client::test_util;
+pub use pull_client::{
+    builder, chain_type, env, fetch_proof, oracle_client, pairs, price_data, pull_service, retry,
+    AptosClient, Client, EvmClient, OracleClient, PartialProof, ProofEnvelope, ProofResponse,
+    SuiClient, DEFAULT_CHUNK_SIZE, DEFAULT_CONCURRENCY,
+};
 
-impl Client {
-    pub async fn new(address: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = PullServiceClient::connect(address).await?;
-        Ok(Self { client })
-    }
-
-    pub async fn get_proof(
-        &mut self,
-        request: &PullRequest,
-    ) -> Result<PullResponse, Box<dyn std::error::Error>> {
-        match self
-            .client
-            .get_proof(tonic::Request::new(request.clone()))
-            .await
-        {
-            Ok(response) => Ok(response.into_inner()),
-            Err(status) => Err(Box::new(status)),
-        }
-    }
-}
+pub mod errors;
+#[cfg(all(feature = "evm", not(target_arch = "wasm32")))]
+pub mod ethereum_connector;
+#[cfg(feature = "evm")]
+pub use pull_core::oracle_proof;
+#[cfg(feature = "evm")]
+pub mod pull_contract;
@@ -1,3 +1,64 @@
-use ethers::contract::abigen;
+use crate::pull_service::PullResponseEvm;
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::providers::bindings::IMulticall3;
+pub use alloy::providers::MULTICALL3_ADDRESS;
+use alloy::sol;
+use alloy::sol_types::{self, SolCall};
+// The offline checks live with the proof types so they build without the client.
+pub use pull_core::verify::{
+    check_and_decode, check_and_decode_for_epoch, check_cluster_inclusion, check_structure,
+    check_structure_for_epoch, InclusionReport,
+};
 
-abigen!(MockOracleClient, "../../resources/abi.json");
+sol!(MockOracleClient, "../../resources/abi.json");
+
+/// A proof submission encoded for a transaction the caller sends itself, e.g. as one call of a
+/// multicall.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateCall {
+    /// The pull contract.
+    pub to: Address,
+    /// ABI-encoded `GetPairPrice` calldata, selector included.
+    pub data: Bytes,
+}
+
+/// Encodes the call the ethereum connector sends to `contract` for `response`, without
+/// touching the network.
+pub fn encode_update(response: &PullResponseEvm, contract: Address) -> UpdateCall {
+    let call = MockOracleClient::GetPairPriceCall {
+        _bytesProof: Bytes::from(response.proof_bytes.clone()),
+        pair: U256::ZERO,
+    };
+    UpdateCall {
+        to: contract,
+        data: call.abi_encode().into(),
+    }
+}
+
+/// Encodes `calls` as a single Multicall3 `aggregate3` call to `multicall`, usually
+/// [`MULTICALL3_ADDRESS`]. Each call may fail without reverting the others.
+pub fn encode_batch(calls: &[UpdateCall], multicall: Address) -> UpdateCall {
+    let call = IMulticall3::aggregate3Call {
+        calls: calls
+            .iter()
+            .map(|call| IMulticall3::Call3 {
+                target: call.to,
+                allowFailure: true,
+                callData: call.data.clone(),
+            })
+            .collect(),
+    };
+    UpdateCall {
+        to: multicall,
+        data: call.abi_encode().into(),
+    }
+}
+
+/// Decodes what a batch from [`encode_batch`] returned to an `eth_call`: for every call, in
+/// order, whether it succeeded and its return or revert data.
+pub fn decode_batch_results(data: &[u8]) -> Result<Vec<(bool, Bytes)>, sol_types::Error> {
+    Ok(IMulticall3::aggregate3Call::abi_decode_returns(data)?
+        .into_iter()
+        .map(|result| (result.success, result.returnData))
+        .collect())
+}
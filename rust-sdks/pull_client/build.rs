@@ -0,0 +1,10 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // tonic's transport (and the generated `connect`) isn't available on wasm32.
+    let wasm = std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32");
+    tonic_build::configure()
+        .build_transport(!wasm)
+        // The messages come from `pull_core`, leaving only the service to generate here.
+        .extern_path(".pull_service", "::pull_core::pull_service")
+        .compile(&["../../protos/client.proto"], &["../../protos"])?; //Path to proto file
+    Ok(())
+}
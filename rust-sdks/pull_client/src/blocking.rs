@@ -0,0 +1,37 @@
+//! Synchronous wrapper around [`crate::Client`] for callers that don't run inside a Tokio runtime.
+
+use crate::builder::ClientBuilder;
+use crate::errors::ClientError;
+use crate::pull_service::{PullRequest, PullResponse};
+use tokio::runtime::{Builder, Runtime};
+
+#[derive(Debug)]
+pub struct Client {
+    inner: crate::Client,
+    rt: Runtime,
+}
+
+impl Client {
+    pub fn new(address: String) -> Result<Self, ClientError> {
+        Self::from_builder(ClientBuilder::new().address(address))
+    }
+
+    /// Connects using the configuration of an async [`ClientBuilder`].
+    pub fn from_builder(builder: ClientBuilder) -> Result<Self, ClientError> {
+        let rt = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(ClientError::Runtime)?;
+        let inner = rt.block_on(builder.build())?;
+        Ok(Self { inner, rt })
+    }
+
+    /// Address of the primary server.
+    pub fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    pub fn get_proof(&mut self, request: &PullRequest) -> Result<PullResponse, ClientError> {
+        self.rt.block_on(self.inner.get_proof(request))
+    }
+}
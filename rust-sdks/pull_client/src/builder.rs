@@ -0,0 +1,497 @@
+use crate::cache::ProofCache;
+use crate::env::Env;
+use crate::errors::ClientError;
+use crate::path_prefix;
+use crate::proof_client::ProofClient;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::proxy::{connector, Proxy};
+use crate::retry::RetryPolicy;
+use crate::{header_names, Client, Transport};
+#[cfg(not(target_arch = "wasm32"))]
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::codec::CompressionEncoding;
+use tonic::codegen::http::uri::{InvalidUri, Uri};
+use tonic::metadata::{MetadataMap, MetadataValue};
+#[cfg(not(target_arch = "wasm32"))]
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
+
+/// Response size limit used unless [`ClientBuilder::max_response_size`] sets another, well
+/// above the few kilobytes a proof for every pair takes.
+pub const DEFAULT_MAX_RESPONSE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Configures and connects a [`Client`].
+#[derive(Clone, Default)]
+pub struct ClientBuilder {
+    address: Option<String>,
+    fallback_addresses: Vec<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    keep_alive_interval: Option<Duration>,
+    keep_alive_timeout: Option<Duration>,
+    keep_alive_while_idle: bool,
+    tcp_keepalive: Option<Duration>,
+    rate_limit: Option<(u64, Duration)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<Proxy>,
+    /// PEM encoded, concatenated.
+    #[cfg(not(target_arch = "wasm32"))]
+    root_certificates: Vec<u8>,
+    default_headers: MetadataMap,
+    auth_token: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    reject_duplicate_pairs: bool,
+    max_pair_index: Option<u32>,
+    cache_ttl: Option<Duration>,
+    disable_gzip: bool,
+    path_prefix: Option<String>,
+    max_response_size: Option<usize>,
+}
+
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("ClientBuilder");
+        f.field("address", &self.address)
+            .field("fallback_addresses", &self.fallback_addresses)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("keep_alive_interval", &self.keep_alive_interval)
+            .field("keep_alive_timeout", &self.keep_alive_timeout)
+            .field("keep_alive_while_idle", &self.keep_alive_while_idle)
+            .field("tcp_keepalive", &self.tcp_keepalive)
+            .field("rate_limit", &self.rate_limit);
+        #[cfg(not(target_arch = "wasm32"))]
+        f.field("proxy", &self.proxy)
+            .field("root_certificates", &self.root_certificates.len());
+        f.field("default_headers", &header_names(&self.default_headers))
+            .field("auth_token", &self.auth_token.as_ref().map(|_| "***"))
+            .field("retry_policy", &self.retry_policy)
+            .field("reject_duplicate_pairs", &self.reject_duplicate_pairs)
+            .field("max_pair_index", &self.max_pair_index)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("accept_gzip", &!self.disable_gzip)
+            .field("path_prefix", &self.path_prefix)
+            .field("max_response_size", &self.max_response_size)
+            .finish()
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from the environment. `SUPRA_ORACLE_URL` holds the server address, or several
+    /// comma-separated ones of which the rest are fallbacks. `SUPRA_ORACLE_TOKEN`, if set, is
+    /// sent as a bearer token.
+    pub fn from_env() -> Result<Self, ClientError> {
+        let mut env = Env::default();
+        let urls = env.required("SUPRA_ORACLE_URL");
+        env.finish().map_err(ClientError::MissingEnv)?;
+
+        let mut addresses = urls.split(',').map(str::trim).filter(|a| !a.is_empty());
+        let mut builder = Self::new();
+        if let Some(primary) = addresses.next() {
+            builder = builder.address(primary);
+        }
+        for fallback in addresses {
+            builder = builder.fallback_address(fallback);
+        }
+        if let Some(token) = Env::optional("SUPRA_ORACLE_TOKEN") {
+            builder = builder.bearer_auth(token);
+        }
+        Ok(builder)
+    }
+
+    /// gRPC server address, e.g. `grpcs://mainnet-dora.supraoracles.com`. `build` fails with
+    /// [`ClientError::InvalidBaseUrl`] unless it is an `http`, `https`, `grpc` or `grpcs` URL
+    /// without a path; a trailing slash is dropped.
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Additional server tried, in the order added, when the previous ones fail with a
+    /// transient error.
+    pub fn fallback_address(mut self, address: impl Into<String>) -> Self {
+        self.fallback_addresses.push(address.into());
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Timeout applied to every request sent through the client. Not available in the browser,
+    /// where the fetch API controls timeouts.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Timeout applied when establishing the connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Sends an HTTP/2 ping every `interval` so a dead connection is noticed, and replaced, before
+    /// the next request instead of during it. Off by default.
+    pub fn keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// How long to wait for a keep-alive ping to be answered before dropping the connection.
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Keeps pinging while no request is in flight, so a poller's connection survives the gaps
+    /// between polls. Only has an effect with [`ClientBuilder::keep_alive_interval`].
+    pub fn keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.keep_alive_while_idle = enabled;
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Enables TCP keepalive probes on the connection, sent after `idle` without traffic.
+    pub fn tcp_keepalive(mut self, idle: Duration) -> Self {
+        self.tcp_keepalive = Some(idle);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Paces outgoing requests to at most `requests` per `period`, waiting for capacity
+    /// instead of failing. Clones of the client share the same budget; each fallback
+    /// server gets its own.
+    pub fn rate_limit(mut self, requests: u64, period: Duration) -> Self {
+        self.rate_limit = Some((requests, period));
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Tunnels connections through an HTTP proxy. Use [`Proxy::from_env`] to honor
+    /// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY`. In the browser the proxy settings of the
+    /// browser apply instead.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Trusts the PEM encoded root certificates in `pem` in addition to the bundled web PKI roots,
+    /// for servers behind a private CA.
+    pub fn add_root_certificate_pem(mut self, pem: impl AsRef<[u8]>) -> Self {
+        self.root_certificates.extend_from_slice(pem.as_ref());
+        self.root_certificates.push(b'\n');
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Like [`ClientBuilder::add_root_certificate_pem`], for a single DER encoded certificate.
+    pub fn add_root_certificate_der(self, der: impl AsRef<[u8]>) -> Self {
+        let body = STANDARD.encode(der);
+        let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+        for line in body.as_bytes().chunks(64) {
+            pem.push_str(std::str::from_utf8(line).expect("base64 is ascii"));
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+        self.add_root_certificate_pem(pem)
+    }
+
+    /// Metadata attached to every request sent through the client.
+    pub fn default_headers(mut self, headers: MetadataMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Sends `authorization: Bearer <token>` with every request.
+    pub fn bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Fail requests that list the same pair index twice instead of sending them.
+    pub fn reject_duplicate_pairs(mut self, reject: bool) -> Self {
+        self.reject_duplicate_pairs = reject;
+        self
+    }
+
+    /// Fail requests for a pair index above `max` with [`ClientError::PairIndexOutOfRange`]
+    /// instead of sending them.
+    pub fn max_pair_index(mut self, max: u32) -> Self {
+        self.max_pair_index = Some(max);
+        self
+    }
+
+    /// Reuses a response for the same chain type and set of pairs for `ttl` instead of asking
+    /// the server again. Clones of the client share the cache, and
+    /// [`Client::get_proof_with_headers`](crate::Client::get_proof_with_headers) bypasses it.
+    /// Off by default.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Sends requests under `prefix`, e.g. `/api/v2`, for a server mounted below the root of its
+    /// host by a gateway or reverse proxy. The gRPC method path is appended to it, so
+    /// `GetProof` goes to `/api/v2/pull_service.PullService/GetProof`. Applies to the fallback
+    /// servers too. The path of the address itself is not used.
+    pub fn path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.path_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Lets the server gzip its responses, which are decompressed transparently. On by default;
+    /// a server that does not compress is unaffected.
+    pub fn accept_gzip(mut self, accept: bool) -> Self {
+        self.disable_gzip = !accept;
+        self
+    }
+
+    /// Largest response accepted, in bytes, [`DEFAULT_MAX_RESPONSE_SIZE`] by default. A larger
+    /// one is refused with [`ClientError::ResponseTooLarge`] instead of being buffered.
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    pub async fn build(mut self) -> Result<Client, ClientError> {
+        let address = self
+            .address
+            .take()
+            .ok_or_else(|| ClientError::InvalidConfig("missing server address".to_string()))?;
+        let address = normalize_address(&address)?;
+        self.fallback_addresses = self
+            .fallback_addresses
+            .iter()
+            .map(|address| normalize_address(address))
+            .collect::<Result<_, _>>()?;
+
+        let mut default_headers = std::mem::take(&mut self.default_headers);
+        if let Some(token) = self.auth_token.take() {
+            let value = MetadataValue::try_from(format!("Bearer {token}")).map_err(|_| {
+                ClientError::InvalidConfig("auth token is not a valid header value".to_string())
+            })?;
+            default_headers.insert("authorization", value);
+        }
+
+        let addresses = std::iter::once(address.clone())
+            .chain(self.fallback_addresses.iter().cloned())
+            .collect();
+        let max_response_size = self.max_response_size.unwrap_or(DEFAULT_MAX_RESPONSE_SIZE);
+        let mut clients: Vec<ProofClient> = self
+            .connect(address)
+            .await?
+            .into_iter()
+            .map(|client| client.max_response_size(max_response_size))
+            .collect();
+        if !self.disable_gzip {
+            clients = clients
+                .into_iter()
+                .map(|client| client.accept_compressed(CompressionEncoding::Gzip))
+                .collect();
+        }
+
+        Ok(Client {
+            clients,
+            addresses,
+            default_headers,
+            retry_policy: self.retry_policy.unwrap_or_else(RetryPolicy::none),
+            reject_duplicate_pairs: self.reject_duplicate_pairs,
+            max_pair_index: self.max_pair_index,
+            cache: self.cache_ttl.map(|ttl| Arc::new(ProofCache::new(ttl))),
+            response: PhantomData,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn connect(&self, address: String) -> Result<Vec<ProofClient>, ClientError> {
+        let prefix = self.prefix()?;
+        let endpoint = self.endpoint(address)?;
+        let primary = match self.proxy_for(&endpoint)? {
+            Some(proxy) => endpoint.connect_with_connector(connector(proxy)).await?,
+            None => endpoint.connect().await?,
+        };
+        let mut clients = vec![ProofClient::new(Transport::new(primary, prefix.clone()))];
+        // Fallbacks connect on first use so an unreachable backup does not fail `build`.
+        for address in &self.fallback_addresses {
+            let endpoint = self.endpoint(address.clone())?;
+            let channel = match self.proxy_for(&endpoint)? {
+                Some(proxy) => endpoint.connect_with_connector_lazy(connector(proxy)),
+                None => endpoint.connect_lazy(),
+            };
+            clients.push(ProofClient::new(Transport::new(channel, prefix.clone())));
+        }
+        Ok(clients)
+    }
+
+    /// In the browser every request is a grpc-web `fetch`, so there is nothing to connect upfront.
+    #[cfg(target_arch = "wasm32")]
+    async fn connect(&self, address: String) -> Result<Vec<ProofClient>, ClientError> {
+        // grpc-web requests go to the address followed by the method path.
+        let prefix = self.prefix()?;
+        let prefix = prefix.as_deref().unwrap_or_default();
+        Ok(std::iter::once(address)
+            .chain(self.fallback_addresses.iter().cloned())
+            .map(|address| {
+                let address = format!("{address}{prefix}");
+                ProofClient::new(Transport::new(address))
+            })
+            .collect())
+    }
+
+    fn prefix(&self) -> Result<Option<Arc<str>>, ClientError> {
+        match &self.path_prefix {
+            Some(prefix) => path_prefix::normalize(prefix),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn proxy_for(&self, endpoint: &Endpoint) -> Result<Option<Uri>, ClientError> {
+        match &self.proxy {
+            Some(proxy) => proxy.for_target(endpoint.uri()),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn endpoint(&self, address: String) -> Result<Endpoint, ClientError> {
+        let mut endpoint = Endpoint::from_shared(address)?;
+        if endpoint.uri().scheme_str() == Some("https") || !self.root_certificates.is_empty() {
+            let mut tls = ClientTlsConfig::new();
+            if !self.root_certificates.is_empty() {
+                tls = tls.ca_certificate(Certificate::from_pem(&self.root_certificates));
+            }
+            endpoint = endpoint.tls_config(tls)?;
+        }
+        if let Some(timeout) = self.timeout {
+            endpoint = endpoint.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        if let Some(interval) = self.keep_alive_interval {
+            endpoint = endpoint.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.keep_alive_timeout {
+            endpoint = endpoint.keep_alive_timeout(timeout);
+        }
+        if self.keep_alive_while_idle {
+            endpoint = endpoint.keep_alive_while_idle(true);
+        }
+        if self.tcp_keepalive.is_some() {
+            endpoint = endpoint.tcp_keepalive(self.tcp_keepalive);
+        }
+        if let Some((requests, period)) = self.rate_limit {
+            endpoint = endpoint.rate_limit(requests, period);
+        }
+        Ok(endpoint)
+    }
+}
+
+/// Reduces `address` to `scheme://host[:port]`, with `grpc` and `grpcs` read as `http` and
+/// `https`. Anything but a trailing slash after the host is rejected: tonic would drop a path
+/// silently, where [`ClientBuilder::path_prefix`] applies it.
+fn normalize_address(address: &str) -> Result<String, ClientError> {
+    let invalid = |reason: &str| ClientError::InvalidBaseUrl {
+        url: address.to_string(),
+        reason: reason.to_string(),
+    };
+    let uri: Uri = address
+        .trim()
+        .parse()
+        .map_err(|e: InvalidUri| invalid(&e.to_string()))?;
+    let scheme = match uri.scheme_str() {
+        Some("http" | "grpc") => "http",
+        Some("https" | "grpcs") => "https",
+        Some(scheme) => return Err(invalid(&format!("unsupported scheme {scheme:?}"))),
+        None => return Err(invalid("missing scheme, e.g. https://")),
+    };
+    let authority = match uri.authority() {
+        Some(authority) if !authority.host().is_empty() => authority,
+        _ => return Err(invalid("missing host")),
+    };
+    if uri.query().is_some() {
+        return Err(invalid("a server address cannot have a query"));
+    }
+    if !uri.path().trim_end_matches('/').is_empty() {
+        return Err(invalid(
+            "a server address cannot have a path, set ClientBuilder::path_prefix instead",
+        ));
+    }
+    Ok(format!("{scheme}://{authority}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_prints_default_header_names_only() {
+        let mut headers = MetadataMap::new();
+        headers.insert("authorization", "Bearer hunter2".parse().unwrap());
+        let builder = ClientBuilder::new()
+            .default_headers(headers)
+            .bearer_auth("s3cret");
+        let debug = format!("{builder:?}");
+        assert!(debug.contains("authorization"), "{debug}");
+        assert!(!debug.contains("hunter2"), "{debug}");
+        assert!(!debug.contains("s3cret"), "{debug}");
+    }
+
+    fn rejection(address: &str) -> String {
+        match normalize_address(address) {
+            Err(ClientError::InvalidBaseUrl { url, reason }) => {
+                assert_eq!(url, address);
+                reason
+            }
+            other => panic!("expected InvalidBaseUrl for {address:?}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn normalize_address_maps_grpc_schemes() {
+        assert_eq!(
+            normalize_address("grpc://localhost:7000").unwrap(),
+            "http://localhost:7000"
+        );
+        assert_eq!(
+            normalize_address("grpcs://rpc.example.com").unwrap(),
+            "https://rpc.example.com"
+        );
+    }
+
+    #[test]
+    fn normalize_address_drops_whitespace_and_trailing_slash() {
+        assert_eq!(
+            normalize_address(" https://rpc.example.com:443/ ").unwrap(),
+            "https://rpc.example.com:443"
+        );
+    }
+
+    #[test]
+    fn normalize_address_rejects_paths_and_queries() {
+        assert!(rejection("https://rpc.example.com/v1").contains("path_prefix"));
+        assert!(rejection("https://rpc.example.com/?a=1").contains("query"));
+    }
+
+    #[test]
+    fn normalize_address_rejects_missing_or_unknown_scheme() {
+        assert!(rejection("rpc.example.com:7000").contains("scheme"));
+        assert!(rejection("ftp://rpc.example.com").contains("\"ftp\""));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::pull_service::{PullRequest, PullResponse};
+use crate::time::Instant;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Chain type, the sorted set of requested pairs and the round, if a past one.
+type Key = (String, Vec<u32>, Option<u64>);
+
+pub(crate) struct ProofCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Key, (Instant, PullResponse)>>,
+}
+
+impl ProofCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, request: &PullRequest) -> Option<PullResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        let key = key(request);
+        match entries.get(&key) {
+            Some((fetched, response)) if fetched.elapsed() < self.ttl => Some(response.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, request: &PullRequest, response: &PullResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        let ttl = self.ttl;
+        entries.retain(|_, (fetched, _)| fetched.elapsed() < ttl);
+        entries.insert(key(request), (Instant::now(), response.clone()));
+    }
+}
+
+fn key(request: &PullRequest) -> Key {
+    let canonical = request.canonical();
+    (
+        canonical.chain_type,
+        canonical.pair_indexes,
+        canonical.round,
+    )
+}
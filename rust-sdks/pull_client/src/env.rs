@@ -0,0 +1,34 @@
+//! Reading configuration from environment variables, shared by
+//! [`ClientBuilder::from_env`](crate::builder::ClientBuilder::from_env) and the connectors of the
+//! per-chain SDKs.
+
+/// Reads variables, remembering the required ones that are unset so they can all be reported
+/// at once.
+#[derive(Default)]
+pub struct Env {
+    missing: Vec<&'static str>,
+}
+
+impl Env {
+    /// The value of `name`, or an empty string if it is unset or empty.
+    pub fn required(&mut self, name: &'static str) -> String {
+        Self::optional(name).unwrap_or_else(|| {
+            self.missing.push(name);
+            String::new()
+        })
+    }
+
+    /// The value of `name`, or `None` if it is unset or empty.
+    pub fn optional(name: &'static str) -> Option<String> {
+        std::env::var(name).ok().filter(|value| !value.is_empty())
+    }
+
+    /// Fails with the names of the required variables that were unset.
+    pub fn finish(self) -> Result<(), Vec<&'static str>> {
+        if self.missing.is_empty() {
+            Ok(())
+        } else {
+            Err(self.missing)
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::retry::DEFAULT_RETRYABLE_CODES;
+pub use pull_core::errors::{DecodeError, InvalidPairTable, UnknownChainType};
+use std::time::Duration;
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+    /// The server rejected the request. `retry_after` is how long it asked the client to wait
+    /// with a `retry-after` header, typically when rate limiting, and `request_id` the id it
+    /// gave the request in an `x-request-id` header, for reference with its operators.
+    #[error(
+        "grpc status {code:?}: {message}{}",
+        request_id.as_ref().map(|id| format!(" (request id {id})")).unwrap_or_default()
+    )]
+    Status {
+        code: tonic::Code,
+        message: String,
+        retry_after: Option<Duration>,
+        request_id: Option<String>,
+    },
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("pair index {index} is above the highest supported index {max}")]
+    PairIndexOutOfRange { index: u32, max: u32 },
+    #[error("invalid client configuration: {0}")]
+    InvalidConfig(String),
+    #[error("invalid server address {url:?}: {reason}")]
+    InvalidBaseUrl { url: String, reason: String },
+    #[error("missing environment variables: {}", .0.join(", "))]
+    MissingEnv(Vec<&'static str>),
+    #[error("failed to start the runtime: {0}")]
+    Runtime(std::io::Error),
+    #[error("unexpected response: {0}")]
+    UnexpectedResponse(String),
+    /// The reply is not a `PullResponse` this SDK can parse, most likely because the server
+    /// runs a newer schema. `raw_body` is the protobuf message as received, e.g. for
+    /// `protoc --decode_raw`.
+    #[error("response does not match the {expected} schema: {reason}")]
+    SchemaMismatch {
+        expected: &'static str,
+        reason: String,
+        raw_body: Vec<u8>,
+    },
+    #[error("response exceeds the {limit}-byte limit")]
+    ResponseTooLarge { limit: usize },
+    #[error("server returned an empty proof")]
+    EmptyProof,
+    #[error("proof is {len} bytes, not a whole number of 32-byte ABI words")]
+    TruncatedProof { len: usize },
+    #[error("deadline passed before the proof arrived")]
+    DeadlineExceeded,
+    #[error("giving up after {attempts} attempts: {last}")]
+    RetriesExhausted {
+        attempts: u32,
+        last: Box<ClientError>,
+    },
+}
+
+impl ClientError {
+    /// Whether the failure is worth retrying: connection errors and the statuses in
+    /// [`DEFAULT_RETRYABLE_CODES`] are, rejected requests are not.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            ClientError::Transport(_) => true,
+            // A well-formed request got a malformed proof, most likely a server-side hiccup.
+            ClientError::EmptyProof | ClientError::TruncatedProof { .. } => true,
+            ClientError::Status { code, .. } => DEFAULT_RETRYABLE_CODES.contains(code),
+            ClientError::InvalidRequest(_)
+            | ClientError::PairIndexOutOfRange { .. }
+            | ClientError::InvalidConfig(_)
+            | ClientError::InvalidBaseUrl { .. }
+            | ClientError::MissingEnv(_)
+            | ClientError::Runtime(_)
+            | ClientError::UnexpectedResponse(_)
+            | ClientError::SchemaMismatch { .. }
+            | ClientError::ResponseTooLarge { .. }
+            | ClientError::DeadlineExceeded
+            | ClientError::RetriesExhausted { .. } => false,
+        }
+    }
+
+    /// How long the server asked to wait before retrying, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ClientError::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// The id the server gave the failed request, if it sent one. For
+    /// [`ClientError::RetriesExhausted`] it is the one of the last attempt.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            ClientError::Status { request_id, .. } => request_id.as_deref(),
+            ClientError::RetriesExhausted { last, .. } => last.request_id(),
+            _ => None,
+        }
+    }
+}
+
+impl From<tonic::Status> for ClientError {
+    fn from(status: tonic::Status) -> Self {
+        ClientError::Status {
+            code: status.code(),
+            message: status.message().to_string(),
+            retry_after: retry_after(status.metadata()),
+            request_id: request_id(status.metadata()),
+        }
+    }
+}
+
+/// Parses a `retry-after` header given in seconds. The HTTP-date form is not supported.
+fn retry_after(metadata: &tonic::metadata::MetadataMap) -> Option<Duration> {
+    let seconds = metadata
+        .get("retry-after")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// Header in which the server returns the id it assigned to a request.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The id the server assigned to the request, from the reply's or the status's metadata.
+pub(crate) fn request_id(metadata: &tonic::metadata::MetadataMap) -> Option<String> {
+    let id = metadata.get(REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
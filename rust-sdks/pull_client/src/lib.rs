@@ -0,0 +1,1400 @@
+//! The gRPC client of the Supra pull service, shared by the per-chain SDKs: retries, failover,
+//! caching and request validation, generic over the chain payload it returns.
+
+use crate::builder::ClientBuilder;
+use crate::cache::ProofCache;
+use crate::chain_type::ChainType;
+use crate::errors::ClientError;
+use crate::proof_client::ProofClient;
+use crate::retry::{RetryPolicy, RetryReport};
+use futures::stream::{self, StreamExt};
+pub use oracle_client::OracleClient;
+use pull_service::pull_response::Resp;
+use pull_service::{
+    PullRequest, PullResponse, PullResponseAptos, PullResponseEvm, PullResponseSui,
+};
+pub use response::ProofResponse;
+use response::{check_proof, check_round};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use tonic::codec::CompressionEncoding;
+use tonic::metadata::{KeyRef, MetadataMap};
+#[cfg(not(target_arch = "wasm32"))]
+use tonic::transport::Channel;
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+pub mod builder;
+mod cache;
+pub use pull_core::chain_type;
+pub mod env;
+pub mod errors;
+pub mod oracle_client;
+pub use pull_core::pairs;
+mod path_prefix;
+pub use pull_core::price_data;
+mod proof_client;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod proxy;
+mod response;
+pub mod retry;
+mod subscription;
+#[cfg(all(any(test, feature = "test-util"), not(target_arch = "wasm32")))]
+pub mod test_util;
+mod time;
+
+pub mod pull_service {
+    pub use pull_core::pull_service::*;
+    tonic::include_proto!("pull_service");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Transport = path_prefix::PrefixedChannel;
+/// Browsers can only speak grpc-web, so the server must accept it when used from wasm.
+#[cfg(target_arch = "wasm32")]
+type Transport = tonic_web_wasm_client::Client;
+
+/// Upper bound on in-flight requests issued by [`Client::get_proofs`].
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+/// Pairs per request sent by [`Client::get_proof_chunked`], below the server's per-request cap.
+pub const DEFAULT_CHUNK_SIZE: usize = 50;
+
+/// Client that returns the payload for the EVM chain.
+pub type EvmClient = Client<PullResponseEvm>;
+/// Client that returns the payload for Aptos.
+pub type AptosClient = Client<PullResponseAptos>;
+/// Client that returns the payload for Sui.
+pub type SuiClient = Client<PullResponseSui>;
+
+/// Outcome of [`Client::get_proof_partial`].
+#[derive(Debug)]
+pub struct PartialProof<R> {
+    /// Proof for `pairs`, or `None` if every pair was rejected.
+    pub proof: Option<R>,
+    /// Pairs covered by `proof`, in request order.
+    pub pairs: Vec<u32>,
+    /// Pairs the server rejected on their own, with its reason.
+    pub rejected: Vec<(u32, ClientError)>,
+}
+
+/// A proof from [`Client::get_proof_detailed`], with where it came from and how long it took.
+#[derive(Clone, Debug)]
+pub struct ProofEnvelope<T> {
+    pub proof: T,
+    /// Address of the server that sent the proof, `None` if it came from the cache or the
+    /// client was built from a channel.
+    pub server: Option<String>,
+    /// Id the server gave the request in an `x-request-id` header.
+    pub request_id: Option<String>,
+    /// Headers of the reply, empty for a cached proof. A proof always comes with an `OK`
+    /// status; failures are returned as [`ClientError::Status`] instead.
+    pub metadata: MetadataMap,
+    /// Time from the call to the proof, retries and backoff included.
+    pub latency: Duration,
+    /// Requests sent for the proof, zero if it came from the cache.
+    pub attempts: usize,
+}
+
+/// The server a fetched proof came from, and the headers it came with.
+struct Origin {
+    server: usize,
+    metadata: MetadataMap,
+}
+
+/// gRPC client for the pull service. `R` picks what `get_proof` returns, see [`ProofResponse`].
+///
+/// Cloning is cheap: clones share the connections, the cache and the rate limit, so clone the
+/// client into each task instead of wrapping it in an `Arc`. It is `Send` and `Sync` outside
+/// the browser.
+pub struct Client<R = PullResponse> {
+    /// The primary server followed by its fallbacks.
+    clients: Vec<ProofClient>,
+    /// Addresses of `clients`, empty for a client built from a channel.
+    addresses: Vec<String>,
+    default_headers: MetadataMap,
+    retry_policy: RetryPolicy,
+    reject_duplicate_pairs: bool,
+    max_pair_index: Option<u32>,
+    cache: Option<Arc<ProofCache>>,
+    response: PhantomData<fn() -> R>,
+}
+
+impl<R> Clone for Client<R> {
+    fn clone(&self) -> Self {
+        Self {
+            clients: self.clients.clone(),
+            addresses: self.addresses.clone(),
+            default_headers: self.default_headers.clone(),
+            retry_policy: self.retry_policy.clone(),
+            reject_duplicate_pairs: self.reject_duplicate_pairs,
+            max_pair_index: self.max_pair_index,
+            cache: self.cache.clone(),
+            response: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+// Clients are shared across tasks by cloning them; keep that possible.
+const _: fn() = || {
+    fn shareable<T: Clone + Send + Sync + 'static>() {}
+    shareable::<Client>();
+    shareable::<EvmClient>();
+};
+
+/// Names of `headers` for `Debug` output. Values such as `authorization` carry credentials.
+pub(crate) fn header_names(headers: &MetadataMap) -> Vec<&str> {
+    headers
+        .keys()
+        .map(|key| match key {
+            KeyRef::Ascii(key) => key.as_str(),
+            KeyRef::Binary(key) => key.as_str(),
+        })
+        .collect()
+}
+
+impl<R> fmt::Debug for Client<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("addresses", &self.addresses)
+            .field("default_headers", &header_names(&self.default_headers))
+            .field("retry_policy", &self.retry_policy)
+            .field("reject_duplicate_pairs", &self.reject_duplicate_pairs)
+            .field("max_pair_index", &self.max_pair_index)
+            .field("cache", &self.cache.is_some())
+            .finish()
+    }
+}
+
+/// Connects to `base_url`, fetches one proof and drops the connection, for scripts and
+/// debugging. The proof comes back as the raw [`PullResponse`], with the payload of whichever
+/// chain was asked for; keep a [`Client`] around to fetch more than once.
+pub async fn fetch_proof(
+    base_url: impl Into<String>,
+    pair_indexes: Vec<u32>,
+    chain_type: ChainType,
+) -> Result<PullResponse, ClientError> {
+    Client::new(base_url.into())
+        .await?
+        .get_proof(&PullRequest::new(pair_indexes, chain_type))
+        .await
+}
+
+impl Client {
+    pub async fn new(address: String) -> Result<Self, ClientError> {
+        ClientBuilder::new().address(address).build().await
+    }
+
+    /// Connects to the servers named in the environment, see [`ClientBuilder::from_env`].
+    pub async fn from_env() -> Result<Self, ClientError> {
+        ClientBuilder::from_env()?.build().await
+    }
+
+    pub async fn with_retry_policy(
+        address: String,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self, ClientError> {
+        ClientBuilder::new()
+            .address(address)
+            .retry_policy(retry_policy)
+            .build()
+            .await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Connects with `timeout` applied both to establishing the connection and to every request.
+    pub async fn with_timeout(address: String, timeout: Duration) -> Result<Self, ClientError> {
+        ClientBuilder::new()
+            .address(address)
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .build()
+            .await
+    }
+
+    /// Connects to the first address and fails over to the others, in order,
+    /// on connection errors and transient server statuses.
+    pub async fn with_addresses(addresses: Vec<String>) -> Result<Self, ClientError> {
+        let mut addresses = addresses.into_iter();
+        let mut builder = ClientBuilder::new();
+        if let Some(primary) = addresses.next() {
+            builder = builder.address(primary);
+        }
+        for fallback in addresses {
+            builder = builder.fallback_address(fallback);
+        }
+        builder.build().await
+    }
+
+    /// Connects to a server that expects `authorization: Bearer <token>` on every request.
+    pub async fn with_auth(address: String, token: String) -> Result<Self, ClientError> {
+        ClientBuilder::new()
+            .address(address)
+            .bearer_auth(token)
+            .build()
+            .await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Reuses an already configured channel, sharing its connection with other clients.
+    pub fn with_channel(channel: Channel) -> Self {
+        Self {
+            clients: vec![
+                ProofClient::new(path_prefix::PrefixedChannel::new(channel, None))
+                    .accept_compressed(CompressionEncoding::Gzip),
+            ],
+            addresses: Vec::new(),
+            default_headers: MetadataMap::new(),
+            retry_policy: RetryPolicy::none(),
+            reject_duplicate_pairs: false,
+            max_pair_index: None,
+            cache: None,
+            response: PhantomData,
+        }
+    }
+}
+
+impl<R: ProofResponse> Client<R> {
+    /// Address of the primary server, or an empty string for a client built with
+    /// [`Client::with_channel`].
+    pub fn base_url(&self) -> &str {
+        self.addresses.first().map_or("", String::as_str)
+    }
+
+    /// The primary server's address followed by the fallbacks'.
+    pub fn addresses(&self) -> &[String] {
+        &self.addresses
+    }
+
+    /// Reuses the connection and settings for a client that returns another response type,
+    /// e.g. an [`EvmClient`].
+    pub fn into_typed<S: ProofResponse>(self) -> Client<S> {
+        Client {
+            clients: self.clients,
+            addresses: self.addresses,
+            default_headers: self.default_headers,
+            retry_policy: self.retry_policy,
+            reject_duplicate_pairs: self.reject_duplicate_pairs,
+            max_pair_index: self.max_pair_index,
+            cache: self.cache,
+            response: PhantomData,
+        }
+    }
+
+    pub async fn get_proof(&mut self, request: &PullRequest) -> Result<R, ClientError> {
+        let retry_policy = self.retry_policy.clone();
+        self.get_proof_with_retry(request, &retry_policy).await
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Like [`Client::get_proof`], but gives up with [`ClientError::DeadlineExceeded`] once
+    /// `deadline` passes, retries included. The in-flight request is cancelled rather than left
+    /// to finish, as it is whenever a `get_proof` future is dropped.
+    pub async fn get_proof_with_deadline(
+        &mut self,
+        request: &PullRequest,
+        deadline: std::time::Instant,
+    ) -> Result<R, ClientError> {
+        tokio::time::timeout_at(deadline.into(), self.get_proof(request))
+            .await
+            .map_err(|_| ClientError::DeadlineExceeded)?
+    }
+
+    /// Like [`Client::get_proof`], sending `headers` along with the client's default headers,
+    /// e.g. a trace id. A header set in both replaces the default one for this call.
+    ///
+    /// The proof cache is bypassed unless `headers` is empty: the server may answer differently
+    /// to other headers, so the proof is neither served from nor stored in it.
+    pub async fn get_proof_with_headers(
+        &mut self,
+        request: &PullRequest,
+        headers: MetadataMap,
+    ) -> Result<R, ClientError> {
+        if headers.is_empty() {
+            return self.get_proof(request).await;
+        }
+        let mut uncached = self.clone();
+        uncached.cache = None;
+        let mut merged = self.default_headers.clone().into_headers();
+        let headers = headers.into_headers();
+        for name in headers.keys() {
+            merged.remove(name);
+        }
+        for (name, value) in &headers {
+            merged.append(name, value.clone());
+        }
+        let retry_policy = self.retry_policy.clone();
+        uncached
+            .fetch_with_retry(request, &retry_policy, &MetadataMap::from_headers(merged))
+            .await
+            .0
+            .map(|(proof, _)| proof)
+    }
+
+    /// Fetches proofs for several independent requests concurrently, keeping the input order.
+    /// One failing request does not affect the others.
+    pub async fn get_proofs(&self, requests: &[PullRequest]) -> Vec<Result<R, ClientError>> {
+        self.get_proofs_with_concurrency(requests, DEFAULT_CONCURRENCY)
+            .await
+    }
+
+    /// Like [`Client::get_proofs`], with at most `concurrency` requests in flight at once.
+    pub async fn get_proofs_with_concurrency(
+        &self,
+        requests: &[PullRequest],
+        concurrency: usize,
+    ) -> Vec<Result<R, ClientError>> {
+        stream::iter(requests)
+            .map(|request| {
+                let mut client = self.clone();
+                async move { client.get_proof(request).await }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Fetches proofs of the same pairs for several chains concurrently, keyed by chain. Each
+    /// value holds that chain's payload variant.
+    pub async fn get_proofs_for_chains(
+        &self,
+        pair_indexes: &[u32],
+        chains: &[ChainType],
+    ) -> HashMap<ChainType, Result<Resp, ClientError>> {
+        let requests: Vec<PullRequest> = chains
+            .iter()
+            .map(|chain| PullRequest::new(pair_indexes.to_vec(), *chain))
+            .collect();
+        let client: Client<Resp> = self.clone().into_typed();
+        chains
+            .iter()
+            .copied()
+            .zip(client.get_proofs(&requests).await)
+            .collect()
+    }
+
+    /// Fetches proofs for any number of pairs by splitting `request.pair_indexes` into requests of
+    /// [`DEFAULT_CHUNK_SIZE`] pairs, sent concurrently.
+    ///
+    /// Each proof is signed on its own and cannot be merged, so one response per chunk is
+    /// returned, in the order of `pair_indexes`. Fails if any chunk fails.
+    pub async fn get_proof_chunked(&self, request: &PullRequest) -> Result<Vec<R>, ClientError> {
+        self.get_proof_chunked_with_size(request, DEFAULT_CHUNK_SIZE)
+            .await
+    }
+
+    /// Like [`Client::get_proof_chunked`], with at most `chunk_size` pairs per request.
+    pub async fn get_proof_chunked_with_size(
+        &self,
+        request: &PullRequest,
+        chunk_size: usize,
+    ) -> Result<Vec<R>, ClientError> {
+        self.validate(request)?;
+        let requests: Vec<PullRequest> = request
+            .pair_indexes
+            .chunks(chunk_size.max(1))
+            .map(|pair_indexes| PullRequest {
+                pair_indexes: pair_indexes.to_vec(),
+                chain_type: request.chain_type.clone(),
+                round: request.round,
+            })
+            .collect();
+        self.get_proofs(&requests).await.into_iter().collect()
+    }
+
+    /// Like [`Client::get_proof`], but pairs the server rejects don't take the others down.
+    ///
+    /// The whole request is tried first. If the server rejects it, each pair is probed on its
+    /// own, concurrently, and a single proof is fetched for the pairs it accepted. Other
+    /// failures, such as connection errors, say nothing about individual pairs and are returned
+    /// as is.
+    pub async fn get_proof_partial(
+        &mut self,
+        request: &PullRequest,
+    ) -> Result<PartialProof<R>, ClientError> {
+        let err = match self.get_proof(request).await {
+            Ok(proof) => {
+                return Ok(PartialProof {
+                    proof: Some(proof),
+                    pairs: request.pair_indexes.clone(),
+                    rejected: Vec::new(),
+                })
+            }
+            Err(err) if is_rejection(&err) => err,
+            Err(err) => return Err(err),
+        };
+        log::warn!(
+            "get_proof rejected, probing {} pairs one by one: {err}",
+            request.pair_indexes.len()
+        );
+
+        let probes: Vec<PullRequest> = request
+            .pair_indexes
+            .iter()
+            .map(|pair| PullRequest {
+                pair_indexes: vec![*pair],
+                chain_type: request.chain_type.clone(),
+                round: request.round,
+            })
+            .collect();
+        let mut pairs = Vec::new();
+        let mut rejected = Vec::new();
+        for (pair, result) in request
+            .pair_indexes
+            .iter()
+            .zip(self.get_proofs(&probes).await)
+        {
+            match result {
+                Ok(_) => pairs.push(*pair),
+                Err(err) if is_rejection(&err) => rejected.push((*pair, err)),
+                Err(err) => return Err(err),
+            }
+        }
+        // Every pair is fine alone, so the request was rejected as a whole.
+        if rejected.is_empty() {
+            return Err(err);
+        }
+
+        let proof = if pairs.is_empty() {
+            None
+        } else {
+            let accepted = PullRequest {
+                pair_indexes: pairs.clone(),
+                chain_type: request.chain_type.clone(),
+                round: request.round,
+            };
+            Some(self.get_proof(&accepted).await?)
+        };
+        Ok(PartialProof {
+            proof,
+            pairs,
+            rejected,
+        })
+    }
+
+    /// Fetches a proof, retrying transient failures according to `retry_policy`.
+    /// Returns `ClientError::RetriesExhausted` with the last error once every attempt has failed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(pair_indexes = ?request.pair_indexes, chain_type = %request.chain_type)
+        )
+    )]
+    pub async fn get_proof_with_retry(
+        &mut self,
+        request: &PullRequest,
+        retry_policy: &RetryPolicy,
+    ) -> Result<R, ClientError> {
+        let headers = self.default_headers.clone();
+        self.fetch_with_retry(request, retry_policy, &headers)
+            .await
+            .0
+            .map(|(proof, _)| proof)
+    }
+
+    /// Like [`Client::get_proof_with_retry`], also reporting how long every attempt took and
+    /// why it failed, to tell a slow server from a failing one.
+    pub async fn get_proof_with_retry_report(
+        &mut self,
+        request: &PullRequest,
+        retry_policy: &RetryPolicy,
+    ) -> (Result<R, ClientError>, RetryReport) {
+        let headers = self.default_headers.clone();
+        let (result, report) = self.fetch_with_retry(request, retry_policy, &headers).await;
+        (result.map(|(proof, _)| proof), report)
+    }
+
+    /// Like [`Client::get_proof`], wrapping the proof in a [`ProofEnvelope`] with the server it
+    /// came from, the reply's headers and request id, and how long fetching it took.
+    pub async fn get_proof_detailed(
+        &mut self,
+        request: &PullRequest,
+    ) -> Result<ProofEnvelope<R>, ClientError> {
+        let started = time::Instant::now();
+        let retry_policy = self.retry_policy.clone();
+        let headers = self.default_headers.clone();
+        let (result, report) = self
+            .fetch_with_retry(request, &retry_policy, &headers)
+            .await;
+        let (proof, origin) = result?;
+        let (server, metadata) = match origin {
+            Some(origin) => (self.addresses.get(origin.server).cloned(), origin.metadata),
+            None => (None, MetadataMap::new()),
+        };
+        Ok(ProofEnvelope {
+            proof,
+            server,
+            request_id: errors::request_id(&metadata),
+            metadata,
+            latency: started.elapsed(),
+            attempts: report.attempts.len(),
+        })
+    }
+
+    async fn fetch_with_retry(
+        &mut self,
+        request: &PullRequest,
+        retry_policy: &RetryPolicy,
+        headers: &MetadataMap,
+    ) -> (Result<(R, Option<Origin>), ClientError>, RetryReport) {
+        #[cfg(feature = "metrics")]
+        let started = time::Instant::now();
+        let mut report = RetryReport::default();
+        let result = self
+            .try_fetch_with_retry(request, retry_policy, headers, &mut report)
+            .await;
+        #[cfg(feature = "metrics")]
+        record_get_proof(&result, started.elapsed());
+        (result, report)
+    }
+
+    async fn try_fetch_with_retry(
+        &mut self,
+        request: &PullRequest,
+        retry_policy: &RetryPolicy,
+        headers: &MetadataMap,
+        report: &mut RetryReport,
+    ) -> Result<(R, Option<Origin>), ClientError> {
+        self.validate(request)?;
+        if let Some(response) = self.cache.as_ref().and_then(|cache| cache.get(request)) {
+            log::debug!("get_proof served from cache");
+            return Ok((R::from_response(response)?, None));
+        }
+        let max_attempts = retry_policy.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let started = time::Instant::now();
+            let result = self.fetch_proof(request, headers).await;
+            report.record(started.elapsed(), result.as_ref().err());
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                attempt,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                ok = result.is_ok(),
+                "get_proof attempt finished"
+            );
+            let err = match result {
+                Ok((response, origin)) => {
+                    if let Some(cache) = &self.cache {
+                        cache.insert(request, &response);
+                    }
+                    return Ok((R::from_response(response)?, Some(origin)));
+                }
+                Err(err) => err,
+            };
+            if !retry_policy.should_retry(&err) || max_attempts == 1 {
+                return Err(err);
+            }
+            if attempt >= max_attempts {
+                return Err(ClientError::RetriesExhausted {
+                    attempts: attempt,
+                    last: Box::new(err),
+                });
+            }
+            let delay = retry_policy.delay_after(&err, attempt);
+            report.retrying_in(delay);
+            log::warn!(
+                "get_proof attempt {attempt}/{max_attempts} failed: {err}, retrying in {delay:?}"
+            );
+            time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Checks that at least one configured server answers, without fetching a proof.
+    ///
+    /// The pull service has no health RPC, so this sends an empty request: any reply, including
+    /// the server rejecting the request, counts as healthy. Rejected credentials are returned
+    /// as is; if every server fails with a connection error or transient status, the last one
+    /// is returned.
+    pub async fn health(&mut self) -> Result<(), ClientError> {
+        let mut last_err = None;
+        for (idx, client) in self.clients.iter_mut().enumerate() {
+            let err = match ping(client, &self.default_headers).await {
+                Ok(()) => return Ok(()),
+                Err(status) => match status.code() {
+                    tonic::Code::Unauthenticated | tonic::Code::PermissionDenied => {
+                        return Err(status.into())
+                    }
+                    _ => ClientError::from(status),
+                },
+            };
+            if !err.is_transient() {
+                return Ok(());
+            }
+            log::warn!("health check of server {idx} failed: {err}");
+            last_err = Some(err);
+        }
+        Err(last_err
+            .unwrap_or_else(|| ClientError::InvalidConfig("no server configured".to_string())))
+    }
+
+    /// Opens the connection to every configured server ahead of the first proof request.
+    ///
+    /// Fallback servers are otherwise only connected to when a request first fails over to
+    /// them. Like [`Client::health`] this sends an empty request, and any reply counts as
+    /// connected. Every server is tried; the first connection error or transient status is
+    /// returned, so an unreachable fallback still leaves the others warm.
+    pub async fn warm_up(&mut self) -> Result<(), ClientError> {
+        let mut first_err = None;
+        for (idx, client) in self.clients.iter_mut().enumerate() {
+            let Err(status) = ping(client, &self.default_headers).await else {
+                continue;
+            };
+            let err = ClientError::from(status);
+            if err.is_transient() {
+                log::warn!("warm-up of server {idx} failed: {err}");
+                first_err.get_or_insert(err);
+            }
+        }
+        first_err.map_or(Ok(()), Err)
+    }
+
+    /// Rejects requests the server would refuse anyway, without a round trip.
+    fn validate(&self, request: &PullRequest) -> Result<(), ClientError> {
+        if let Some(expected) = R::chain_type() {
+            if request.chain_type.parse::<ChainType>().ok() != Some(expected) {
+                return Err(ClientError::InvalidRequest(format!(
+                    "chain_type {:?} does not match the {expected} client",
+                    request.chain_type
+                )));
+            }
+        }
+        if request.pair_indexes.is_empty() {
+            return Err(ClientError::InvalidRequest(
+                "pair_indexes is empty".to_string(),
+            ));
+        }
+        #[cfg(not(feature = "evm"))]
+        if request.round.is_some()
+            && request.chain_type.parse::<ChainType>().ok() == Some(ChainType::Evm)
+        {
+            return Err(ClientError::InvalidRequest(
+                "the round of an EVM proof can only be checked with the `evm` feature".to_string(),
+            ));
+        }
+        if let Some(max) = self.max_pair_index {
+            if let Some(&index) = request.pair_indexes.iter().find(|pair| **pair > max) {
+                return Err(ClientError::PairIndexOutOfRange { index, max });
+            }
+        }
+        if self.reject_duplicate_pairs {
+            let mut seen = HashSet::new();
+            if let Some(pair) = request.pair_indexes.iter().find(|pair| !seen.insert(*pair)) {
+                return Err(ClientError::InvalidRequest(format!(
+                    "pair index {pair} is requested more than once"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn fetch_proof(
+        &mut self,
+        request: &PullRequest,
+        headers: &MetadataMap,
+    ) -> Result<(PullResponse, Origin), ClientError> {
+        let mut last_err = None;
+        for (idx, client) in self.clients.iter_mut().enumerate() {
+            #[cfg(feature = "tracing")]
+            let started = time::Instant::now();
+            let result = client
+                .get_proof(tonic::Request::from_parts(
+                    headers.clone(),
+                    tonic::Extensions::default(),
+                    request.clone(),
+                ))
+                .await;
+            #[cfg(feature = "tracing")]
+            match &result {
+                Ok(response) => tracing::debug!(
+                    server = idx,
+                    request_id = errors::request_id(response.metadata()),
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "proof fetched"
+                ),
+                Err(status) => tracing::warn!(
+                    server = idx,
+                    code = ?status.code(),
+                    request_id = errors::request_id(status.metadata()),
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "proof fetch failed"
+                ),
+            }
+            let err = match result {
+                Ok(response) => {
+                    let (metadata, body, _) = response.into_parts();
+                    let request_id = errors::request_id(&metadata);
+                    let err = match proof_client::decode(body) {
+                        Ok(response) => match check_proof(&response)
+                            .and_then(|()| check_round(request, &response))
+                        {
+                            Ok(()) => {
+                                let origin = Origin {
+                                    server: idx,
+                                    metadata,
+                                };
+                                return Ok((response, origin));
+                            }
+                            Err(err) => err,
+                        },
+                        Err(err) => err,
+                    };
+                    // Errors about a reply carry no id of their own, so log the one it came with.
+                    if let Some(request_id) = request_id {
+                        log::warn!(
+                            "unusable reply from server {idx}, request id {request_id}: {err}"
+                        );
+                    }
+                    err
+                }
+                Err(status) => client.error(status),
+            };
+            if !err.is_transient() {
+                return Err(err);
+            }
+            log::warn!("get_proof via server {idx} failed: {err}");
+            last_err = Some(err);
+        }
+        Err(last_err
+            .unwrap_or_else(|| ClientError::InvalidConfig("no server configured".to_string())))
+    }
+}
+
+#[cfg(feature = "metrics")]
+/// Counts one `get_proof` call, retries included, and records how long it took.
+fn record_get_proof<R>(result: &Result<R, ClientError>, elapsed: std::time::Duration) {
+    let outcome = match result {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    };
+    metrics::counter!("supra_get_proof_total", "result" => outcome).increment(1);
+    if let Err(err) = result {
+        // The last attempt's status, so retried calls are counted by how they finally failed.
+        let status = match err {
+            ClientError::RetriesExhausted { last, .. } => last.as_ref(),
+            err => err,
+        };
+        let status = match status {
+            ClientError::Status { code, .. } => format!("{code:?}"),
+            #[cfg(not(target_arch = "wasm32"))]
+            ClientError::Transport(_) => "transport".to_string(),
+            _ => "client".to_string(),
+        };
+        metrics::counter!("supra_get_proof_errors_total", "status" => status).increment(1);
+    }
+    metrics::histogram!("supra_get_proof_duration_seconds").record(elapsed.as_secs_f64());
+}
+
+/// Whether the server refused the request because of what it asked for, as opposed to who
+/// asked or how the connection went.
+fn is_rejection(err: &ClientError) -> bool {
+    matches!(
+        err,
+        ClientError::Status {
+            code: tonic::Code::InvalidArgument
+                | tonic::Code::NotFound
+                | tonic::Code::OutOfRange
+                | tonic::Code::FailedPrecondition,
+            ..
+        }
+    )
+}
+
+/// Sends `client` an empty request, the closest the pull service has to a ping.
+async fn ping(client: &mut ProofClient, headers: &MetadataMap) -> Result<(), tonic::Status> {
+    client
+        .get_proof(tonic::Request::from_parts(
+            headers.clone(),
+            tonic::Extensions::default(),
+            PullRequest::default(),
+        ))
+        .await
+        .map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    // Not a glob: `OracleClient::get_proof` takes `&self` and would shadow the inherent method.
+    use super::{
+        ChainType, Channel, Client, ClientBuilder, ClientError, Duration, MetadataMap, PullRequest,
+        PullResponse, PullResponseEvm, Resp, RetryPolicy,
+    };
+    use crate::test_util::MockServer;
+    use tonic::{Code, Status};
+
+    fn proof(pair_indexes: Vec<u32>) -> PullResponse {
+        PullResponse {
+            resp: Some(Resp::Evm(PullResponseEvm {
+                pair_indexes,
+                proof_bytes: vec![0; 32],
+            })),
+        }
+    }
+
+    fn request(pair_indexes: Vec<u32>) -> PullRequest {
+        PullRequest::new(pair_indexes, ChainType::Evm)
+    }
+
+    fn fast_retries(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(
+            max_attempts,
+            Duration::from_millis(1),
+            Duration::from_millis(1),
+            1.0,
+        )
+    }
+
+    #[tokio::test]
+    async fn get_proof_retries_transient_statuses() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        server.push_reply(Err(Status::unavailable("restarting")));
+        server.push_reply(Err(Status::unavailable("restarting")));
+        let mut client = Client::with_retry_policy(server.address(), fast_retries(3))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            client.get_proof(&request(vec![1])).await.unwrap(),
+            proof(vec![1])
+        );
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_proof_gives_up_after_max_attempts() {
+        let server = MockServer::start(Err(Status::unavailable("down")))
+            .await
+            .unwrap();
+        let mut client = Client::with_retry_policy(server.address(), fast_retries(2))
+            .await
+            .unwrap();
+
+        let err = client.get_proof(&request(vec![1])).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::RetriesExhausted { attempts: 2, .. }
+        ));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_proof_does_not_retry_rejected_requests() {
+        let server = MockServer::start(Err(Status::invalid_argument("unknown pair")))
+            .await
+            .unwrap();
+        let mut client = Client::with_retry_policy(server.address(), fast_retries(3))
+            .await
+            .unwrap();
+
+        let err = client.get_proof(&request(vec![1])).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::Status {
+                code: Code::InvalidArgument,
+                ..
+            }
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn with_channel_fetches_over_the_given_channel() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let channel = Channel::from_shared(server.address())
+            .unwrap()
+            .connect_lazy();
+        let mut client = Client::with_channel(channel);
+
+        assert_eq!(
+            client.get_proof(&request(vec![1])).await.unwrap(),
+            proof(vec![1])
+        );
+        assert_eq!(client.base_url(), "");
+    }
+
+    #[tokio::test]
+    async fn get_proof_fails_over_to_the_next_server() {
+        let primary = MockServer::start(Err(Status::unavailable("down")))
+            .await
+            .unwrap();
+        let fallback = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = Client::with_addresses(vec![primary.address(), fallback.address()])
+            .await
+            .unwrap();
+
+        let envelope = client.get_proof_detailed(&request(vec![1])).await.unwrap();
+        assert_eq!(envelope.server, Some(fallback.address()));
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(fallback.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_proof_does_not_fail_over_rejected_requests() {
+        let primary = MockServer::start(Err(Status::not_found("unknown pair")))
+            .await
+            .unwrap();
+        let fallback = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = Client::with_addresses(vec![primary.address(), fallback.address()])
+            .await
+            .unwrap();
+
+        assert!(client.get_proof(&request(vec![1])).await.is_err());
+        assert!(fallback.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_proof_chunked_returns_one_proof_per_chunk_in_order() {
+        let server = MockServer::start(Err(Status::internal("unexpected chunk")))
+            .await
+            .unwrap();
+        for chunk in [vec![1, 2], vec![3, 4], vec![5]] {
+            server.set_reply_for(&chunk, Ok(proof(chunk.clone())));
+        }
+        let client = Client::new(server.address()).await.unwrap();
+
+        let proofs = client
+            .get_proof_chunked_with_size(&request(vec![1, 2, 3, 4, 5]), 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            proofs,
+            vec![proof(vec![1, 2]), proof(vec![3, 4]), proof(vec![5])]
+        );
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_proof_chunked_fails_if_a_chunk_fails() {
+        let server = MockServer::start(Ok(proof(vec![1, 2]))).await.unwrap();
+        server.set_reply_for(&[3], Err(Status::not_found("unknown pair")));
+        let client = Client::new(server.address()).await.unwrap();
+
+        assert!(client
+            .get_proof_chunked_with_size(&request(vec![1, 2, 3]), 2)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn cached_proofs_are_served_without_a_request() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = ClientBuilder::new()
+            .address(server.address())
+            .cache_ttl(Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+
+        client.get_proof(&request(vec![1])).await.unwrap();
+        let envelope = client.get_proof_detailed(&request(vec![1])).await.unwrap();
+        assert_eq!(envelope.attempts, 0);
+        assert_eq!(server.requests().len(), 1);
+
+        client.get_proof(&request(vec![2])).await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_fetches_are_not_cached() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        server.push_reply(Err(Status::not_found("not yet")));
+        let mut client = ClientBuilder::new()
+            .address(server.address())
+            .cache_ttl(Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+
+        assert!(client.get_proof(&request(vec![1])).await.is_err());
+        client.get_proof(&request(vec![1])).await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn health_counts_a_rejected_request_as_healthy() {
+        let server = MockServer::start(Err(Status::invalid_argument("pair_indexes is empty")))
+            .await
+            .unwrap();
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        client.health().await.unwrap();
+        assert_eq!(server.requests(), vec![PullRequest::default()]);
+    }
+
+    #[tokio::test]
+    async fn health_returns_rejected_credentials() {
+        let server = MockServer::start(Err(Status::unauthenticated("bad token")))
+            .await
+            .unwrap();
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        assert!(matches!(
+            client.health().await,
+            Err(ClientError::Status {
+                code: Code::Unauthenticated,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn health_tries_the_fallbacks() {
+        let primary = MockServer::start(Err(Status::unavailable("down")))
+            .await
+            .unwrap();
+        let fallback = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = Client::with_addresses(vec![primary.address(), fallback.address()])
+            .await
+            .unwrap();
+
+        client.health().await.unwrap();
+        assert_eq!(fallback.requests().len(), 1);
+
+        fallback.set_reply(Err(Status::unavailable("down")));
+        assert!(matches!(
+            client.health().await,
+            Err(ClientError::Status {
+                code: Code::Unavailable,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn retry_after_sets_the_delay_up_to_max_delay() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut retry_after = MetadataMap::new();
+        retry_after.insert("retry-after", "60".parse().unwrap());
+        server.set_metadata(retry_after);
+        server.push_reply(Err(Status::resource_exhausted("slow down")));
+        let retry_policy =
+            RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(20), 1.0);
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        let (result, report) = client
+            .get_proof_with_retry_report(&request(vec![1]), &retry_policy)
+            .await;
+        result.unwrap();
+        assert_eq!(report.attempts[0].retry_in, Some(Duration::from_millis(20)));
+    }
+
+    #[tokio::test]
+    async fn get_proof_with_headers_overrides_default_headers() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut defaults = MetadataMap::new();
+        defaults.insert("x-client", "sdk".parse().unwrap());
+        defaults.insert("x-trace-id", "default".parse().unwrap());
+        let mut client = ClientBuilder::new()
+            .address(server.address())
+            .default_headers(defaults)
+            .build()
+            .await
+            .unwrap();
+
+        let mut headers = MetadataMap::new();
+        headers.insert("x-trace-id", "call".parse().unwrap());
+        client
+            .get_proof_with_headers(&request(vec![1]), headers)
+            .await
+            .unwrap();
+
+        let sent = &server.request_metadata()[0];
+        assert_eq!(sent.get("x-client").unwrap(), "sdk");
+        assert_eq!(
+            sent.get_all("x-trace-id").iter().collect::<Vec<_>>(),
+            vec!["call"]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_proof_with_headers_bypasses_the_cache() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = ClientBuilder::new()
+            .address(server.address())
+            .cache_ttl(Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+        let mut headers = MetadataMap::new();
+        headers.insert("x-trace-id", "call".parse().unwrap());
+
+        client.get_proof(&request(vec![1])).await.unwrap();
+        client
+            .get_proof_with_headers(&request(vec![1]), headers.clone())
+            .await
+            .unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        server.set_reply(Err(Status::unavailable("down")));
+        client.get_proof(&request(vec![1])).await.unwrap();
+        assert!(client
+            .get_proof_with_headers(&request(vec![1]), headers)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn get_proof_partial_drops_the_rejected_pairs() {
+        let server = MockServer::start(Ok(proof(vec![0]))).await.unwrap();
+        server.set_reply_for(&[1, 2, 3], Err(Status::invalid_argument("unknown pair 2")));
+        server.set_reply_for(&[2], Err(Status::invalid_argument("unknown pair 2")));
+        server.set_reply_for(&[1, 3], Ok(proof(vec![1, 3])));
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        let partial = client
+            .get_proof_partial(&request(vec![1, 2, 3]))
+            .await
+            .unwrap();
+        assert_eq!(partial.proof, Some(proof(vec![1, 3])));
+        assert_eq!(partial.pairs, vec![1, 3]);
+        assert_eq!(partial.rejected.len(), 1);
+        assert_eq!(partial.rejected[0].0, 2);
+    }
+
+    #[tokio::test]
+    async fn get_proof_partial_returns_the_whole_proof_when_nothing_fails() {
+        let server = MockServer::start(Ok(proof(vec![1, 2]))).await.unwrap();
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        let partial = client
+            .get_proof_partial(&request(vec![1, 2]))
+            .await
+            .unwrap();
+        assert_eq!(partial.proof, Some(proof(vec![1, 2])));
+        assert!(partial.rejected.is_empty());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_proof_partial_returns_the_rejection_if_every_pair_is_fine_alone() {
+        let server = MockServer::start(Ok(proof(vec![0]))).await.unwrap();
+        server.set_reply_for(&[1, 2], Err(Status::out_of_range("too many pairs")));
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        assert!(matches!(
+            client.get_proof_partial(&request(vec![1, 2])).await,
+            Err(ClientError::Status {
+                code: Code::OutOfRange,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_proof_partial_returns_other_failures_as_is() {
+        let server = MockServer::start(Err(Status::unauthenticated("bad token")))
+            .await
+            .unwrap();
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        assert!(client
+            .get_proof_partial(&request(vec![1, 2]))
+            .await
+            .is_err());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_proof_with_deadline_gives_up_on_a_slow_server() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        server.set_delay(Duration::from_secs(5));
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_millis(50);
+        assert!(matches!(
+            client
+                .get_proof_with_deadline(&request(vec![1]), deadline)
+                .await,
+            Err(ClientError::DeadlineExceeded)
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_proof_with_deadline_returns_a_proof_in_time() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        assert_eq!(
+            client
+                .get_proof_with_deadline(&request(vec![1]), deadline)
+                .await
+                .unwrap(),
+            proof(vec![1])
+        );
+    }
+
+    #[tokio::test]
+    async fn gzip_is_accepted_unless_disabled() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut gzip = Client::new(server.address()).await.unwrap();
+        let mut plain = ClientBuilder::new()
+            .address(server.address())
+            .accept_gzip(false)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            gzip.get_proof(&request(vec![1])).await.unwrap(),
+            proof(vec![1])
+        );
+        assert_eq!(
+            plain.get_proof(&request(vec![1])).await.unwrap(),
+            proof(vec![1])
+        );
+        let accepted: Vec<_> = server
+            .request_metadata()
+            .iter()
+            .map(|headers| headers.get("grpc-accept-encoding").cloned())
+            .collect();
+        assert!(accepted[0]
+            .as_ref()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("gzip"));
+        assert_eq!(accepted[1], None);
+    }
+
+    #[tokio::test]
+    async fn path_prefix_reaches_a_server_mounted_below_the_root() {
+        let server = MockServer::start_with_path_prefix("/api/v2", Ok(proof(vec![1])))
+            .await
+            .unwrap();
+        let mut prefixed = ClientBuilder::new()
+            .address(server.address())
+            .path_prefix("api/v2/")
+            .build()
+            .await
+            .unwrap();
+        let mut unprefixed = Client::new(server.address()).await.unwrap();
+
+        assert_eq!(
+            prefixed.get_proof(&request(vec![1])).await.unwrap(),
+            proof(vec![1])
+        );
+        assert!(matches!(
+            unprefixed.get_proof(&request(vec![1])).await,
+            Err(ClientError::Status {
+                code: Code::Unimplemented,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn undecodable_replies_are_schema_mismatches() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        // Field 1, length-delimited, claiming 5 bytes that never come.
+        let body = vec![0x0a, 0x05, 0x01];
+        server.set_raw_reply(body.clone());
+        let mut client = Client::new(server.address()).await.unwrap();
+
+        let err = client.get_proof(&request(vec![1])).await.unwrap_err();
+        let ClientError::SchemaMismatch { raw_body, .. } = err else {
+            panic!("expected a schema mismatch, got {err}");
+        };
+        assert_eq!(raw_body, body);
+    }
+
+    #[tokio::test]
+    async fn max_response_size_rejects_larger_replies() {
+        let large = PullResponse {
+            resp: Some(Resp::Evm(PullResponseEvm {
+                pair_indexes: vec![1],
+                proof_bytes: vec![0; 32 * 1024],
+            })),
+        };
+        let server = MockServer::start(Ok(large.clone())).await.unwrap();
+        let build = |gzip| {
+            ClientBuilder::new()
+                .address(server.address())
+                .accept_gzip(gzip)
+                .max_response_size(1024)
+                .build()
+        };
+
+        // Zeros gzip to far below the limit, so the decompressed size is what counts.
+        for gzip in [false, true] {
+            let mut client = build(gzip).await.unwrap();
+            assert!(matches!(
+                client.get_proof(&request(vec![1])).await,
+                Err(ClientError::ResponseTooLarge { limit: 1024 })
+            ));
+        }
+
+        let mut client = ClientBuilder::new()
+            .address(server.address())
+            .max_response_size(64 * 1024)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(client.get_proof(&request(vec![1])).await.unwrap(), large);
+    }
+
+    #[tokio::test]
+    async fn warm_up_reaches_every_server() {
+        let primary = MockServer::start(Err(Status::invalid_argument("pair_indexes is empty")))
+            .await
+            .unwrap();
+        let fallback = MockServer::start(Err(Status::invalid_argument("pair_indexes is empty")))
+            .await
+            .unwrap();
+        let mut client = Client::with_addresses(vec![primary.address(), fallback.address()])
+            .await
+            .unwrap();
+
+        client.warm_up().await.unwrap();
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(fallback.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn warm_up_returns_the_first_failure_after_trying_every_server() {
+        let primary = MockServer::start(Err(Status::unavailable("down")))
+            .await
+            .unwrap();
+        let fallback = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut client = Client::with_addresses(vec![primary.address(), fallback.address()])
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            client.warm_up().await,
+            Err(ClientError::Status {
+                code: Code::Unavailable,
+                ..
+            })
+        ));
+        assert_eq!(fallback.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn the_request_id_is_kept_on_proofs_and_errors() {
+        let server = MockServer::start(Ok(proof(vec![1]))).await.unwrap();
+        let mut request_id = MetadataMap::new();
+        request_id.insert("x-request-id", "req-42".parse().unwrap());
+        server.set_metadata(request_id);
+        server.push_reply(Err(Status::unavailable("down")));
+        server.push_reply(Err(Status::unavailable("down")));
+        let mut client = Client::with_retry_policy(server.address(), fast_retries(2))
+            .await
+            .unwrap();
+
+        let err = client.get_proof(&request(vec![1])).await.unwrap_err();
+        assert!(matches!(err, ClientError::RetriesExhausted { .. }));
+        assert_eq!(err.request_id(), Some("req-42"));
+        assert!(err.to_string().contains("(request id req-42)"));
+
+        let envelope = client.get_proof_detailed(&request(vec![1])).await.unwrap();
+        assert_eq!(envelope.request_id.as_deref(), Some("req-42"));
+    }
+}
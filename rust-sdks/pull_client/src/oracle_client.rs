@@ -0,0 +1,30 @@
+//! A chain-agnostic interface over the pull clients.
+
+use crate::errors::ClientError;
+use crate::pull_service::PullRequest;
+use crate::{Client, ProofResponse};
+
+/// Fetches proofs without tying the caller to a concrete client, so code can hold a
+/// `Box<dyn OracleClient<Response = PullResponseEvm, Error = ClientError>>` and pick the
+/// chain or the client at runtime.
+#[cfg_attr(not(target_arch = "wasm32"), tonic::async_trait)]
+#[cfg_attr(target_arch = "wasm32", tonic::async_trait(?Send))]
+pub trait OracleClient {
+    type Response;
+    type Error;
+
+    async fn get_proof(&self, request: &PullRequest) -> Result<Self::Response, Self::Error>;
+}
+
+/// Runs [`Client::get_proof`] on a clone of the client, which shares its connections.
+#[cfg_attr(not(target_arch = "wasm32"), tonic::async_trait)]
+#[cfg_attr(target_arch = "wasm32", tonic::async_trait(?Send))]
+impl<R: ProofResponse + Send + 'static> OracleClient for Client<R> {
+    type Response = R;
+    type Error = ClientError;
+
+    async fn get_proof(&self, request: &PullRequest) -> Result<R, ClientError> {
+        // Spelled out: method syntax would pick this trait method again, not the inherent one.
+        Client::get_proof(&mut self.clone(), request).await
+    }
+}
@@ -0,0 +1,75 @@
+//! Request paths for servers mounted below the root of their host.
+
+use crate::errors::ClientError;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use tonic::body::BoxBody;
+use tonic::codegen::http::uri::PathAndQuery;
+#[cfg(not(target_arch = "wasm32"))]
+use tonic::codegen::http::{Request, Uri};
+#[cfg(not(target_arch = "wasm32"))]
+use tonic::transport::Channel;
+#[cfg(not(target_arch = "wasm32"))]
+use tower::Service;
+
+/// A [`Channel`] that puts a fixed prefix in front of every request path, e.g. turning
+/// `/pull_service.PullService/GetProof` into `/api/v2/pull_service.PullService/GetProof`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub(crate) struct PrefixedChannel {
+    channel: Channel,
+    prefix: Option<Arc<str>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PrefixedChannel {
+    pub(crate) fn new(channel: Channel, prefix: Option<Arc<str>>) -> Self {
+        Self { channel, prefix }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Service<Request<BoxBody>> for PrefixedChannel {
+    type Response = <Channel as Service<Request<BoxBody>>>::Response;
+    type Error = <Channel as Service<Request<BoxBody>>>::Error;
+    type Future = <Channel as Service<Request<BoxBody>>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.channel.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<BoxBody>) -> Self::Future {
+        if let Some(prefix) = &self.prefix {
+            let mut parts = request.uri().clone().into_parts();
+            let path = parts
+                .path_and_query
+                .as_ref()
+                .map_or("/", PathAndQuery::as_str);
+            // The prefix was checked by `normalize`, so this only fails on a malformed path.
+            if let Ok(path) = PathAndQuery::try_from(format!("{prefix}{path}")) {
+                parts.path_and_query = Some(path);
+                if let Ok(uri) = Uri::from_parts(parts) {
+                    *request.uri_mut() = uri;
+                }
+            }
+        }
+        self.channel.call(request)
+    }
+}
+
+/// Trims `prefix` to a leading slash and no trailing one, with `None` for the root.
+pub(crate) fn normalize(prefix: &str) -> Result<Option<Arc<str>>, ClientError> {
+    let trimmed = prefix.trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    let prefix = format!("/{trimmed}");
+    if prefix.contains(['?', '#']) || PathAndQuery::try_from(prefix.as_str()).is_err() {
+        return Err(ClientError::InvalidConfig(format!(
+            "invalid path prefix: {prefix}"
+        )));
+    }
+    Ok(Some(prefix.into()))
+}
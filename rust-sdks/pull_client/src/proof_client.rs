@@ -0,0 +1,141 @@
+//! The `GetProof` call, decoding the reply only once its raw bytes are in hand.
+//!
+//! The generated `PullServiceClient` decodes inside tonic and turns a reply it cannot parse
+//! into a bare `Internal` status. Reading the body first keeps it for
+//! [`ClientError::SchemaMismatch`], so version skew between the server and the SDK can be
+//! diagnosed from the error alone.
+
+use crate::builder::DEFAULT_MAX_RESPONSE_SIZE;
+use crate::errors::ClientError;
+use crate::pull_service::{PullRequest, PullResponse};
+use crate::Transport;
+use prost::bytes::{Buf, Bytes};
+use prost::Message;
+use tonic::client::Grpc;
+use tonic::codec::{Codec, CompressionEncoding, DecodeBuf, Decoder, ProstCodec};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::StdError;
+use tonic::GrpcMethod;
+use tonic::{Code, Request, Response, Status};
+
+const GET_PROOF: &str = "/pull_service.PullService/GetProof";
+
+/// Stand-in for the generated `PullServiceClient` that returns the undecoded reply.
+#[derive(Clone, Debug)]
+pub(crate) struct ProofClient {
+    inner: Grpc<Transport>,
+    max_response_size: usize,
+}
+
+impl ProofClient {
+    pub(crate) fn new(transport: Transport) -> Self {
+        Self {
+            inner: Grpc::new(transport).max_decoding_message_size(DEFAULT_MAX_RESPONSE_SIZE),
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+        }
+    }
+
+    /// tonic checks the size of the message as sent, the decoder the size once decompressed.
+    pub(crate) fn max_response_size(mut self, limit: usize) -> Self {
+        self.inner = self.inner.max_decoding_message_size(limit);
+        self.max_response_size = limit;
+        self
+    }
+
+    pub(crate) fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+        self.inner = self.inner.accept_compressed(encoding);
+        self
+    }
+
+    /// Sends `request` and returns the reply's encoded `PullResponse`, see [`decode`].
+    pub(crate) async fn get_proof(
+        &mut self,
+        mut request: Request<PullRequest>,
+    ) -> Result<Response<Bytes>, Status> {
+        self.inner.ready().await.map_err(|e| {
+            Status::new(
+                Code::Unknown,
+                format!("Service was not ready: {}", StdError::from(e)),
+            )
+        })?;
+        request
+            .extensions_mut()
+            .insert(GrpcMethod::new("pull_service.PullService", "GetProof"));
+        self.inner
+            .unary(
+                request,
+                PathAndQuery::from_static(GET_PROOF),
+                RawCodec {
+                    limit: self.max_response_size,
+                },
+            )
+            .await
+    }
+
+    /// The error for a failed [`ProofClient::get_proof`].
+    pub(crate) fn error(&self, status: Status) -> ClientError {
+        // Raised by tonic or `RawDecoder` before the server's own statuses could be seen.
+        if status.code() == Code::OutOfRange && status.message().starts_with(TOO_LARGE) {
+            return ClientError::ResponseTooLarge {
+                limit: self.max_response_size,
+            };
+        }
+        status.into()
+    }
+}
+
+/// Parses a reply from [`ProofClient::get_proof`].
+pub(crate) fn decode(raw_body: Bytes) -> Result<PullResponse, ClientError> {
+    PullResponse::decode(raw_body.clone()).map_err(|e| ClientError::SchemaMismatch {
+        expected: "PullResponse",
+        reason: e.to_string(),
+        raw_body: raw_body.to_vec(),
+    })
+}
+
+/// How tonic words the status for a message over the size limit.
+const TOO_LARGE: &str = "Error, message length too large";
+
+/// Encodes requests like the generated client, and hands replies back as they arrived.
+struct RawCodec {
+    limit: usize,
+}
+
+impl Codec for RawCodec {
+    type Encode = PullRequest;
+    type Decode = Bytes;
+    type Encoder = <ProstCodec<PullRequest, PullResponse> as Codec>::Encoder;
+    type Decoder = RawDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        ProstCodec::<PullRequest, PullResponse>::default().encoder()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawDecoder { limit: self.limit }
+    }
+}
+
+struct RawDecoder {
+    limit: usize,
+}
+
+impl Decoder for RawDecoder {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Bytes>, Status> {
+        // A gzipped reply can decompress to far more than tonic's check on its sent length.
+        if buf.remaining() > self.limit {
+            return Err(Status::new(
+                Code::OutOfRange,
+                format!(
+                    "{TOO_LARGE}: decompressed to {} bytes, the limit is: {} bytes",
+                    buf.remaining(),
+                    self.limit
+                ),
+            ));
+        }
+        Ok(Some(buf.copy_to_bytes(buf.remaining())))
+    }
+}
@@ -0,0 +1,173 @@
+use crate::chain_type::ChainType;
+use crate::errors::ClientError;
+use crate::pull_service::pull_response::Resp;
+use crate::pull_service::{
+    PullRequest, PullResponse, PullResponseAptos, PullResponseEvm, PullResponseSui,
+};
+
+/// What a [`Client`](crate::Client) returns from `get_proof`: the raw [`PullResponse`], or the
+/// payload for one chain.
+pub trait ProofResponse: Sized {
+    /// The chain requests must be for, if the response type is tied to one.
+    fn chain_type() -> Option<ChainType>;
+
+    fn from_response(response: PullResponse) -> Result<Self, ClientError>;
+}
+
+impl ProofResponse for PullResponse {
+    fn chain_type() -> Option<ChainType> {
+        None
+    }
+
+    fn from_response(response: PullResponse) -> Result<Self, ClientError> {
+        Ok(response)
+    }
+}
+
+/// The payload of whichever chain the request was for.
+impl ProofResponse for Resp {
+    fn chain_type() -> Option<ChainType> {
+        None
+    }
+
+    fn from_response(response: PullResponse) -> Result<Self, ClientError> {
+        response
+            .resp
+            .ok_or_else(|| ClientError::UnexpectedResponse("response has no proof".to_string()))
+    }
+}
+
+/// Rejects a proof that cannot be what the server meant to send, before it is cached or
+/// handed to a connector.
+pub(crate) fn check_proof(response: &PullResponse) -> Result<(), ClientError> {
+    match &response.resp {
+        Some(Resp::Evm(evm)) if evm.proof_bytes.is_empty() => Err(ClientError::EmptyProof),
+        Some(Resp::Evm(evm)) if evm.proof_bytes.len() % 32 != 0 => {
+            Err(ClientError::TruncatedProof {
+                len: evm.proof_bytes.len(),
+            })
+        }
+        // Every field is a BCS vector, which encodes to at least its length prefix.
+        Some(Resp::Aptos(aptos)) if aptos.sig.is_empty() => Err(ClientError::EmptyProof),
+        Some(Resp::Sui(sui)) if sui.sig.is_empty() => Err(ClientError::EmptyProof),
+        _ => Ok(()),
+    }
+}
+
+/// Rejects a proof for another round than the one `request` pinned. A server that keeps no
+/// history ignores the round and answers with its latest proof instead of failing.
+pub(crate) fn check_round(
+    request: &PullRequest,
+    response: &PullResponse,
+) -> Result<(), ClientError> {
+    let Some(round) = request.round else {
+        return Ok(());
+    };
+    let prices = match &response.resp {
+        #[cfg(feature = "evm")]
+        Some(Resp::Evm(evm)) => evm.decode_prices(),
+        // `Client::validate` refuses to pin the round of a proof it cannot read.
+        #[cfg(not(feature = "evm"))]
+        Some(Resp::Evm(_)) => return Ok(()),
+        Some(Resp::Aptos(aptos)) => aptos.decode_prices(),
+        Some(Resp::Sui(sui)) => sui.decode_prices(),
+        None => return Ok(()),
+    };
+    let prices = prices.map_err(|err| {
+        ClientError::UnexpectedResponse(format!("cannot read the round of the proof: {err}"))
+    })?;
+    match prices.iter().find(|price| price.round != round) {
+        Some(price) => Err(ClientError::UnexpectedResponse(format!(
+            "asked for the proof of round {round}, got one of round {}; the server may keep no \
+             history",
+            price.round
+        ))),
+        None => Ok(()),
+    }
+}
+
+macro_rules! chain_response {
+    ($ty:ty, $variant:ident, $chain:expr) => {
+        impl ProofResponse for $ty {
+            fn chain_type() -> Option<ChainType> {
+                Some($chain)
+            }
+
+            fn from_response(response: PullResponse) -> Result<Self, ClientError> {
+                match response.resp {
+                    Some(Resp::$variant(payload)) => Ok(payload),
+                    _ => Err(ClientError::UnexpectedResponse(format!(
+                        "expected a {} proof",
+                        $chain
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+chain_response!(PullResponseAptos, Aptos, ChainType::Aptos);
+chain_response!(PullResponseEvm, Evm, ChainType::Evm);
+chain_response!(PullResponseSui, Sui, ChainType::Sui);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evm(proof_bytes: Vec<u8>) -> PullResponse {
+        PullResponse {
+            resp: Some(Resp::Evm(PullResponseEvm {
+                pair_indexes: vec![0],
+                proof_bytes,
+            })),
+        }
+    }
+
+    #[test]
+    fn check_proof_accepts_whole_abi_words() {
+        assert!(check_proof(&evm(vec![0; 64])).is_ok());
+    }
+
+    #[test]
+    fn check_proof_rejects_empty_and_truncated_evm_proofs() {
+        assert!(matches!(
+            check_proof(&evm(Vec::new())),
+            Err(ClientError::EmptyProof)
+        ));
+        assert!(matches!(
+            check_proof(&evm(vec![0; 33])),
+            Err(ClientError::TruncatedProof { len: 33 })
+        ));
+    }
+
+    #[test]
+    fn check_proof_rejects_move_proofs_without_signatures() {
+        let aptos = PullResponse {
+            resp: Some(Resp::Aptos(PullResponseAptos::default())),
+        };
+        let sui = PullResponse {
+            resp: Some(Resp::Sui(PullResponseSui::default())),
+        };
+        assert!(matches!(check_proof(&aptos), Err(ClientError::EmptyProof)));
+        assert!(matches!(check_proof(&sui), Err(ClientError::EmptyProof)));
+
+        let signed = PullResponse {
+            resp: Some(Resp::Sui(PullResponseSui {
+                sig: vec![0],
+                ..Default::default()
+            })),
+        };
+        assert!(check_proof(&signed).is_ok());
+    }
+
+    #[test]
+    fn check_proof_leaves_missing_proofs_to_the_caller() {
+        assert!(check_proof(&PullResponse { resp: None }).is_ok());
+    }
+
+    #[test]
+    fn check_round_skips_requests_for_the_latest_proof() {
+        let request = PullRequest::new(vec![0], ChainType::Evm);
+        assert!(check_round(&request, &evm(vec![0; 64])).is_ok());
+    }
+}
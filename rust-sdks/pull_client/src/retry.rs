@@ -0,0 +1,284 @@
+use crate::errors::ClientError;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::Code;
+
+/// Statuses retried unless [`RetryPolicy::with_retryable_codes`] says otherwise.
+pub const DEFAULT_RETRYABLE_CODES: &[Code] = &[
+    Code::Unavailable,
+    Code::ResourceExhausted,
+    Code::DeadlineExceeded,
+    Code::Aborted,
+    Code::Internal,
+    Code::Unknown,
+];
+
+/// Exponential backoff settings applied to transient `get_proof` failures.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// Randomizes each delay, see [`RetryPolicy::with_jitter`]. Off by default.
+    pub jitter: Option<Jitter>,
+    /// Statuses worth another attempt, [`DEFAULT_RETRYABLE_CODES`] by default.
+    pub retryable_codes: Vec<Code>,
+}
+
+impl RetryPolicy {
+    pub fn new(
+        max_attempts: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+        multiplier: f64,
+    ) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+            multiplier,
+            jitter: None,
+            retryable_codes: DEFAULT_RETRYABLE_CODES.to_vec(),
+        }
+    }
+
+    /// A policy that makes a single attempt and never retries.
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO, Duration::ZERO, 1.0)
+    }
+
+    /// Waits a random delay between zero and the computed backoff ("full jitter"), so clients
+    /// that failed together don't all retry at the same moment.
+    pub fn with_jitter(mut self) -> Self {
+        self.jitter = Some(Jitter::new());
+        self
+    }
+
+    /// Like [`RetryPolicy::with_jitter`], drawing the delays from `seed` so they repeat from
+    /// run to run, e.g. in tests.
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter = Some(Jitter::seeded(seed));
+        self
+    }
+
+    /// Retries only statuses in `codes`. Connection errors and malformed proofs are retried
+    /// regardless. A `retry-after` header sets the delay before retrying a status in `codes`,
+    /// capped at `max_delay`; it does not make other statuses retryable.
+    pub fn with_retryable_codes(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.retryable_codes = codes.into_iter().collect();
+        self
+    }
+
+    /// Whether `err` is worth another attempt under this policy.
+    pub(crate) fn should_retry(&self, err: &ClientError) -> bool {
+        match err {
+            ClientError::Status { code, .. } => self.retryable_codes.contains(code),
+            err => err.is_transient(),
+        }
+    }
+
+    /// Delay to wait before retrying after `err`: the server's `retry-after`, capped at
+    /// `max_delay`, or else [`RetryPolicy::delay_for`].
+    pub(crate) fn delay_after(&self, err: &ClientError, retry: u32) -> Duration {
+        match err.retry_after() {
+            Some(retry_after) => retry_after.min(self.max_delay),
+            None => self.delay_for(retry),
+        }
+    }
+
+    /// Delay to wait before the given retry, where `retry` starts at 1.
+    pub(crate) fn delay_for(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(retry.saturating_sub(1) as i32);
+        let backoff = self.base_delay.mul_f64(factor).min(self.max_delay);
+        match &self.jitter {
+            Some(jitter) => backoff.mul_f64(jitter.next_fraction()),
+            None => backoff,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(200), Duration::from_secs(5), 2.0)
+    }
+}
+
+/// How each attempt of a retried `get_proof` went, from
+/// [`Client::get_proof_with_retry_report`](crate::Client::get_proof_with_retry_report).
+///
+/// Empty if no request was sent, e.g. because the proof came from the cache or the request was
+/// rejected before sending.
+#[derive(Clone, Debug, Default)]
+pub struct RetryReport {
+    pub attempts: Vec<Attempt>,
+}
+
+/// One attempt of a retried `get_proof`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attempt {
+    /// How long the attempt took, fallback servers included.
+    pub duration: Duration,
+    /// Why the attempt failed, `None` if it returned the proof.
+    pub error: Option<String>,
+    /// How long the client waited before the next attempt, `None` if there was none.
+    pub retry_in: Option<Duration>,
+}
+
+impl RetryReport {
+    /// Time spent on the attempts and the waits between them.
+    pub fn elapsed(&self) -> Duration {
+        self.attempts
+            .iter()
+            .map(|attempt| attempt.duration + attempt.retry_in.unwrap_or_default())
+            .sum()
+    }
+
+    pub(crate) fn record(&mut self, duration: Duration, error: Option<&ClientError>) {
+        self.attempts.push(Attempt {
+            duration,
+            error: error.map(ToString::to_string),
+            retry_in: None,
+        });
+    }
+
+    pub(crate) fn retrying_in(&mut self, delay: Duration) {
+        if let Some(attempt) = self.attempts.last_mut() {
+            attempt.retry_in = Some(delay);
+        }
+    }
+}
+
+/// Source of the random fractions [`RetryPolicy`] scales its delays by. Clones share one
+/// sequence.
+#[derive(Clone, Debug)]
+pub struct Jitter {
+    state: Arc<AtomicU64>,
+}
+
+impl Jitter {
+    /// Seeded differently by every process.
+    pub fn new() -> Self {
+        Self::seeded(RandomState::new().build_hasher().finish())
+    }
+
+    /// Produces the same sequence for the same `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            state: Arc::new(AtomicU64::new(seed)),
+        }
+    }
+
+    /// A value in `[0, 1)`, from the SplitMix64 generator.
+    fn next_fraction(&self) -> f64 {
+        let mut z = self
+            .state
+            .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // The top 53 bits fill an f64 mantissa exactly.
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Default for Jitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(5, Duration::from_millis(100), Duration::from_secs(1), 2.0)
+    }
+
+    fn status(code: Code, retry_after: Option<Duration>) -> ClientError {
+        ClientError::Status {
+            code,
+            message: String::new(),
+            retry_after,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn delay_for_backs_off_up_to_max_delay() {
+        let delays: Vec<Duration> = (1..=5).map(|retry| policy().delay_for(retry)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1000].map(Duration::from_millis)
+        );
+    }
+
+    #[test]
+    fn delay_for_treats_multiplier_below_one_as_one() {
+        let mut policy = policy();
+        policy.multiplier = 0.5;
+        assert_eq!(policy.delay_for(4), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn jitter_follows_splitmix64() {
+        // The first two SplitMix64 outputs for seed 0.
+        let jitter = Jitter::seeded(0);
+        for expected in [0xe220_a839_7b1d_cdaf_u64, 0x6e78_9e6a_a1b9_65f4] {
+            assert_eq!(
+                jitter.next_fraction(),
+                (expected >> 11) as f64 / (1u64 << 53) as f64
+            );
+        }
+    }
+
+    #[test]
+    fn seeded_jitter_repeats_and_stays_below_backoff() {
+        let first = policy().with_jitter_seed(7);
+        let second = policy().with_jitter_seed(7);
+        for retry in 1..=5 {
+            let delay = first.delay_for(retry);
+            assert_eq!(delay, second.delay_for(retry));
+            assert!(delay < policy().delay_for(retry));
+        }
+    }
+
+    #[test]
+    fn should_retry_only_retryable_codes() {
+        let policy = policy().with_retryable_codes([Code::Unavailable]);
+        assert!(policy.should_retry(&status(Code::Unavailable, None)));
+        assert!(!policy.should_retry(&status(Code::Internal, None)));
+        assert!(policy.should_retry(&ClientError::EmptyProof));
+        assert!(!policy.should_retry(&ClientError::InvalidRequest(String::new())));
+    }
+
+    #[test]
+    fn should_retry_ignores_retry_after_of_other_codes() {
+        let retry_after = Some(Duration::from_secs(1));
+        assert!(!policy().should_retry(&status(Code::NotFound, retry_after)));
+        assert!(policy().should_retry(&status(Code::Unavailable, retry_after)));
+    }
+
+    #[test]
+    fn default_policy_retries_rate_limiting() {
+        let err = status(Code::ResourceExhausted, Some(Duration::from_millis(300)));
+        assert!(policy().should_retry(&err));
+        assert_eq!(policy().delay_after(&err, 1), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn delay_after_caps_retry_after_at_max_delay() {
+        let err = status(Code::Unavailable, Some(Duration::from_secs(3600)));
+        assert_eq!(policy().delay_after(&err, 1), Duration::from_secs(1));
+        let err = status(Code::Unavailable, None);
+        assert_eq!(policy().delay_after(&err, 3), Duration::from_millis(400));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::errors::ClientError;
+use crate::pull_service::PullRequest;
+use crate::retry::RetryPolicy;
+use crate::{time, Client, ProofResponse};
+use futures::stream::{self, Stream, StreamExt};
+use std::pin::Pin;
+use std::time::Duration;
+
+struct Subscription<S, R> {
+    polls: Pin<Box<S>>,
+    last_proof: Option<R>,
+    failures: u32,
+    backoff: Option<Duration>,
+}
+
+struct Poll<R> {
+    client: Client<R>,
+    request: PullRequest,
+    next_tick: Option<time::Instant>,
+}
+
+impl<R: ProofResponse> Client<R> {
+    /// Yields a new proof for `request` whenever the server has one, checking every `interval`.
+    ///
+    /// This is [`Client::poll`] with unchanged proofs skipped. Failures are yielded as `Err`
+    /// items and the stream keeps going, backing off exponentially (up to 5s, or `interval` if
+    /// longer) until a fetch succeeds again. A wait the server asks for with `retry-after`
+    /// replaces the backoff, up to 5s too. Drop the stream to stop.
+    pub fn subscribe(
+        &self,
+        request: PullRequest,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<R, ClientError>>
+    where
+        R: Clone + PartialEq,
+    {
+        let policy = RetryPolicy::default();
+        let subscription = Subscription {
+            polls: Box::pin(self.poll(request, interval)),
+            last_proof: None,
+            failures: 0,
+            backoff: None,
+        };
+        stream::unfold(subscription, move |mut sub| {
+            let policy = policy.clone();
+            async move {
+                loop {
+                    if let Some(backoff) = sub.backoff.take() {
+                        time::sleep(backoff).await;
+                    }
+                    let item = sub.polls.next().await?;
+                    match &item {
+                        Ok(proof) => {
+                            sub.failures = 0;
+                            if sub.last_proof.as_ref() == Some(proof) {
+                                continue;
+                            }
+                            sub.last_proof = Some(proof.clone());
+                        }
+                        Err(err) => {
+                            sub.failures += 1;
+                            // The poller waits for its next tick anyway, so a backoff shorter
+                            // than `interval` adds nothing.
+                            sub.backoff = Some(policy.delay_after(err, sub.failures));
+                            log::warn!("subscription fetch failed {} times in a row", sub.failures);
+                        }
+                    }
+                    return Some((item, sub));
+                }
+            }
+        })
+    }
+
+    /// Fetches a proof for `request` every `interval` and yields each result, starting right
+    /// away.
+    ///
+    /// Unlike [`Client::subscribe`], every proof is yielded, changed or not, and failures don't
+    /// slow the poller down: an `Err` item is yielded and the next fetch happens on the next tick.
+    /// A fetch that overruns `interval` skips the ticks it missed instead of firing them in a
+    /// burst. Drop the stream to stop.
+    pub fn poll(
+        &self,
+        request: PullRequest,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<R, ClientError>> {
+        let poll = Poll {
+            client: self.clone(),
+            request,
+            next_tick: None,
+        };
+        stream::unfold(poll, move |mut poll| async move {
+            let now = time::Instant::now();
+            match poll.next_tick {
+                Some(tick) if tick > now => {
+                    time::sleep(tick - now).await;
+                    poll.next_tick = Some(tick + interval);
+                }
+                _ => poll.next_tick = Some(now + interval),
+            }
+            let item = poll.client.get_proof(&poll.request).await;
+            if let Err(err) = &item {
+                log::warn!("poll fetch failed: {err}");
+            }
+            Some((item, poll))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_type::ChainType;
+    use crate::pull_service::pull_response::Resp;
+    use crate::pull_service::{PullResponse, PullResponseEvm};
+    use crate::test_util::MockServer;
+    use crate::EvmClient;
+    use futures::StreamExt;
+    use tonic::Status;
+
+    fn proof(proof_bytes: Vec<u8>) -> PullResponse {
+        PullResponse {
+            resp: Some(Resp::Evm(PullResponseEvm {
+                pair_indexes: vec![1],
+                proof_bytes,
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_yields_every_result_and_keeps_going_after_failures() {
+        let server = MockServer::start(Ok(proof(vec![0; 32]))).await.unwrap();
+        server.push_reply(Err(Status::unavailable("restarting")));
+        let client = Client::new(server.address()).await.unwrap();
+
+        let items: Vec<_> = client
+            .poll(
+                PullRequest::new(vec![1], ChainType::Evm),
+                Duration::from_millis(5),
+            )
+            .take(3)
+            .collect()
+            .await;
+        assert!(items[0].is_err());
+        assert_eq!(items[1].as_ref().unwrap(), &proof(vec![0; 32]));
+        assert_eq!(items[2].as_ref().unwrap(), &proof(vec![0; 32]));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn subscribe_skips_unchanged_proofs_and_keeps_going_after_failures() {
+        let server = MockServer::start(Ok(proof(vec![2; 32]))).await.unwrap();
+        server.push_reply(Ok(proof(vec![1; 32])));
+        server.push_reply(Ok(proof(vec![1; 32])));
+        server.push_reply(Err(Status::unavailable("restarting")));
+        server.push_reply(Ok(proof(vec![1; 32])));
+        let client: EvmClient = Client::new(server.address()).await.unwrap().into_typed();
+
+        let items: Vec<_> = client
+            .subscribe(
+                PullRequest::new(vec![1], ChainType::Evm),
+                Duration::from_millis(5),
+            )
+            .take(3)
+            .collect()
+            .await;
+        assert_eq!(items[0].as_ref().unwrap().proof_bytes, vec![1; 32]);
+        assert!(items[1].is_err());
+        assert_eq!(items[2].as_ref().unwrap().proof_bytes, vec![2; 32]);
+        assert_eq!(server.requests().len(), 5);
+    }
+}
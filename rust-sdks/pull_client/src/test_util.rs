@@ -0,0 +1,379 @@
+//! An in-process pull service for testing code that depends on [`crate::Client`].
+
+use crate::pull_service::{PullRequest, PullResponse};
+use prost::bytes::{Buf, BufMut};
+use prost::encoding::{DecodeContext, WireType};
+use prost::{DecodeError, Message};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::body::BoxBody;
+use tonic::codec::{CompressionEncoding, ProstCodec};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::http::{Request as HttpRequest, Response as HttpResponse, Uri};
+use tonic::codegen::{empty_body, Body, BoxFuture, StdError};
+use tonic::metadata::MetadataMap;
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Extensions, Request, Response, Status};
+use tower::Service;
+
+const GET_PROOF: &str = "/pull_service.PullService/GetProof";
+
+/// What the server answers a `GetProof` request with.
+#[derive(Clone)]
+enum Reply {
+    /// An encoded `PullResponse`, or bytes that need not decode as one.
+    Body(Vec<u8>),
+    Status(Status),
+}
+
+impl From<Result<PullResponse, Status>> for Reply {
+    fn from(reply: Result<PullResponse, Status>) -> Self {
+        match reply {
+            Ok(response) => Reply::Body(response.encode_to_vec()),
+            Err(status) => Reply::Status(status),
+        }
+    }
+}
+
+struct State {
+    reply: Reply,
+    queued: VecDeque<Reply>,
+    by_pairs: Vec<(Vec<u32>, Reply)>,
+    metadata: MetadataMap,
+    delay: Duration,
+    requests: Vec<PullRequest>,
+    request_metadata: Vec<MetadataMap>,
+}
+
+/// An already encoded message, sent as is.
+#[derive(Clone, Debug, Default)]
+struct Encoded(Vec<u8>);
+
+impl Message for Encoded {
+    fn encode_raw<B: BufMut>(&self, buf: &mut B) {
+        buf.put_slice(&self.0);
+    }
+
+    fn merge_field<B: Buf>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        prost::encoding::skip_field(wire_type, tag, buf, ctx)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Hand-written in place of the generated `PullServiceServer`, which can only send a
+/// [`PullResponse`] and not the raw bytes of [`MockServer::set_raw_reply`].
+#[derive(Clone)]
+struct MockPullService {
+    state: Arc<Mutex<State>>,
+}
+
+impl NamedService for MockPullService {
+    const NAME: &'static str = "pull_service.PullService";
+}
+
+impl UnaryService<PullRequest> for MockPullService {
+    type Response = Encoded;
+    type Future = BoxFuture<Response<Encoded>, Status>;
+
+    fn call(&mut self, request: Request<PullRequest>) -> Self::Future {
+        let state = self.state.clone();
+        Box::pin(async move {
+            let (reply, metadata, delay) = {
+                let mut state = state.lock().unwrap();
+                let (request_metadata, _, request) = request.into_parts();
+                let reply = state.queued.pop_front().unwrap_or_else(|| {
+                    state
+                        .by_pairs
+                        .iter()
+                        .find(|(pairs, _)| *pairs == request.pair_indexes)
+                        .map_or_else(|| state.reply.clone(), |(_, reply)| reply.clone())
+                });
+                state.requests.push(request);
+                state.request_metadata.push(request_metadata);
+                (reply, state.metadata.clone(), state.delay)
+            };
+            tokio::time::sleep(delay).await;
+            let body = match reply {
+                Reply::Body(bytes) => Encoded(bytes),
+                Reply::Status(mut status) => {
+                    let mut headers = status.metadata().clone().into_headers();
+                    headers.extend(metadata.into_headers());
+                    *status.metadata_mut() = MetadataMap::from_headers(headers);
+                    return Err(status);
+                }
+            };
+            Ok(Response::from_parts(metadata, body, Extensions::default()))
+        })
+    }
+}
+
+impl<B> Service<HttpRequest<B>> for MockPullService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = HttpResponse<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move {
+            if request.uri().path() != GET_PROOF {
+                return Ok(HttpResponse::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .unwrap());
+            }
+            let mut grpc = Grpc::new(ProstCodec::<Encoded, PullRequest>::default())
+                .accept_compressed(CompressionEncoding::Gzip)
+                .send_compressed(CompressionEncoding::Gzip);
+            Ok(grpc.unary(service, request).await)
+        })
+    }
+}
+
+/// Serves only requests whose path starts with `prefix`, as a server mounted below the root of
+/// its host would, handing them on without it.
+#[derive(Clone)]
+struct StripPrefix<S> {
+    inner: S,
+    prefix: Arc<str>,
+}
+
+impl<S, B> Service<HttpRequest<B>> for StripPrefix<S>
+where
+    S: Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        let mut parts = request.uri().clone().into_parts();
+        let path = parts
+            .path_and_query
+            .as_ref()
+            .map_or("/", PathAndQuery::as_str);
+        // A path without the prefix is sent to `/`, where nothing is served.
+        let stripped = path.strip_prefix(&*self.prefix).unwrap_or("/");
+        if let Ok(path) = PathAndQuery::try_from(stripped) {
+            parts.path_and_query = Some(path);
+            if let Ok(uri) = Uri::from_parts(parts) {
+                *request.uri_mut() = uri;
+            }
+        }
+        self.inner.call(request)
+    }
+}
+
+/// Serves canned `GetProof` replies on `127.0.0.1` until dropped.
+pub struct MockServer {
+    address: String,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free local port that answers every request with `reply`.
+    pub async fn start(reply: Result<PullResponse, Status>) -> io::Result<Self> {
+        Self::start_with_path_prefix("", reply).await
+    }
+
+    /// Like [`MockServer::start`], serving only below `prefix`, e.g. `/api/v2`, for clients
+    /// built with [`crate::builder::ClientBuilder::path_prefix`].
+    pub async fn start_with_path_prefix(
+        prefix: &str,
+        reply: Result<PullResponse, Status>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = format!("http://{}", listener.local_addr()?);
+        let incoming =
+            TcpIncoming::from_listener(listener, true, None).map_err(io::Error::other)?;
+
+        let state = Arc::new(Mutex::new(State {
+            reply: reply.into(),
+            queued: VecDeque::new(),
+            by_pairs: Vec::new(),
+            metadata: MetadataMap::new(),
+            delay: Duration::ZERO,
+            requests: Vec::new(),
+            request_metadata: Vec::new(),
+        }));
+        let service = MockPullService {
+            state: state.clone(),
+        };
+        let prefix: Arc<str> = prefix.trim_end_matches('/').into();
+        let (shutdown, signal) = oneshot::channel();
+        tokio::spawn(async move {
+            let served = Server::builder()
+                .layer(tower::layer::layer_fn(move |inner| StripPrefix {
+                    inner,
+                    prefix: prefix.clone(),
+                }))
+                .add_service(service)
+                .serve_with_incoming_shutdown(incoming, async {
+                    let _ = signal.await;
+                })
+                .await;
+            if let Err(err) = served {
+                log::warn!("mock pull server stopped: {err}");
+            }
+        });
+
+        Ok(Self {
+            address,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Address to pass to [`crate::Client::new`].
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    /// Replaces the reply for subsequent requests, e.g. with `Err(Status::unavailable(..))`.
+    pub fn set_reply(&self, reply: Result<PullResponse, Status>) {
+        self.state.lock().unwrap().reply = reply.into();
+    }
+
+    /// Answers requests for exactly `pair_indexes`, in that order, with `reply` instead of the
+    /// one from [`MockServer::set_reply`], e.g. to tell concurrent requests apart.
+    pub fn set_reply_for(&self, pair_indexes: &[u32], reply: Result<PullResponse, Status>) {
+        let mut state = self.state.lock().unwrap();
+        state.by_pairs.retain(|(pairs, _)| pairs != pair_indexes);
+        state.by_pairs.push((pair_indexes.to_vec(), reply.into()));
+    }
+
+    /// Replies to subsequent requests with `bytes` as the encoded `PullResponse`, e.g. to
+    /// send one the client cannot decode.
+    pub fn set_raw_reply(&self, bytes: Vec<u8>) {
+        self.state.lock().unwrap().reply = Reply::Body(bytes);
+    }
+
+    /// Answers the next request with `reply`, once, ahead of the one from
+    /// [`MockServer::set_reply`]. Queued replies are used in the order they were pushed.
+    pub fn push_reply(&self, reply: Result<PullResponse, Status>) {
+        self.state.lock().unwrap().queued.push_back(reply.into());
+    }
+
+    /// Headers sent with every subsequent reply, e.g. `retry-after` or `x-request-id`. On an
+    /// error status they are added to the status's own.
+    pub fn set_metadata(&self, metadata: MetadataMap) {
+        self.state.lock().unwrap().metadata = metadata;
+    }
+
+    /// Holds every subsequent reply back for `delay`.
+    pub fn set_delay(&self, delay: Duration) {
+        self.state.lock().unwrap().delay = delay;
+    }
+
+    /// Requests received so far, in arrival order.
+    pub fn requests(&self) -> Vec<PullRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Headers of the requests received so far, in arrival order.
+    pub fn request_metadata(&self) -> Vec<MetadataMap> {
+        self.state.lock().unwrap().request_metadata.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_type::ChainType;
+    use crate::errors::ClientError;
+    use crate::pull_service::pull_response::Resp;
+    use crate::pull_service::PullResponseEvm;
+    use crate::Client;
+
+    fn proof() -> PullResponse {
+        PullResponse {
+            resp: Some(Resp::Evm(PullResponseEvm {
+                pair_indexes: vec![1],
+                proof_bytes: vec![0; 32],
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_replies_come_before_the_standing_one() {
+        let server = MockServer::start(Ok(proof())).await.unwrap();
+        server.push_reply(Err(Status::not_found("no such pair")));
+        let mut client = Client::new(server.address()).await.unwrap();
+        let request = PullRequest::new(vec![1], ChainType::Evm);
+
+        assert!(matches!(
+            client.get_proof(&request).await,
+            Err(ClientError::Status {
+                code: tonic::Code::NotFound,
+                ..
+            })
+        ));
+        assert_eq!(client.get_proof(&request).await.unwrap(), proof());
+        assert_eq!(server.requests(), vec![request.clone(), request]);
+    }
+
+    #[tokio::test]
+    async fn replies_carry_the_set_metadata() {
+        let server = MockServer::start(Err(Status::unavailable("down")))
+            .await
+            .unwrap();
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-request-id", "req-7".parse().unwrap());
+        server.set_metadata(metadata);
+        let mut client = Client::new(server.address()).await.unwrap();
+        let request = PullRequest::new(vec![1], ChainType::Evm);
+
+        let ClientError::Status { request_id, .. } = client.get_proof(&request).await.unwrap_err()
+        else {
+            panic!("expected a status");
+        };
+        assert_eq!(request_id.as_deref(), Some("req-7"));
+
+        server.set_reply(Ok(proof()));
+        let envelope = client.get_proof_detailed(&request).await.unwrap();
+        assert_eq!(envelope.request_id.as_deref(), Some("req-7"));
+    }
+}
@@ -0,0 +1,7 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    prost_build::Config::new()
+        // prost already derives `PartialEq`; every field also supports `Eq` and `Hash`.
+        .type_attribute(".pull_service", "#[derive(Eq, Hash)]")
+        .compile_protos(&["../../protos/client.proto"], &["../../protos"])?; //Path to proto file
+    Ok(())
+}
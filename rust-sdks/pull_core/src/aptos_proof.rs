@@ -0,0 +1,182 @@
+//! The BCS-encoded payload of a [`PullResponseAptos`], decoded.
+
+use crate::errors::DecodeError;
+use crate::pull_service::PullResponseAptos;
+
+/// The components of an Aptos pull proof, as the entry function receives them.
+///
+/// Each `bytes` field of [`PullResponseAptos`] is a BCS-encoded Move vector; they are parallel,
+/// so the `scc_*` fields all hold one entry per cluster, with the prices of a cluster's pairs
+/// nested inside.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AptosProof {
+    pub pair_indexes: Vec<u32>,
+    pub dkg_object: String,
+    pub oracle_holder_object: String,
+
+    // One entry per vote.
+    pub vote_smr_block_round: Vec<Vec<u8>>,
+    pub vote_smr_block_timestamp: Vec<Vec<u8>>,
+    pub vote_smr_block_author: Vec<Vec<u8>>,
+    pub vote_smr_block_qc_hash: Vec<Vec<u8>>,
+    pub vote_smr_block_batch_hashes: Vec<Vec<Vec<u8>>>,
+    pub vote_round: Vec<u64>,
+
+    // One entry per SMR batch.
+    pub min_batch_protocol: Vec<Vec<u8>>,
+    pub min_batch_txn_hashes: Vec<Vec<Vec<u8>>>,
+
+    // One entry per SMR transaction.
+    pub min_txn_cluster_hashes: Vec<Vec<Vec<u8>>>,
+    pub min_txn_sender: Vec<Vec<u8>>,
+    pub min_txn_protocol: Vec<Vec<u8>>,
+    pub min_txn_tx_sub_type: Vec<u8>,
+
+    // One entry per signed coherent cluster.
+    pub scc_data_hash: Vec<Vec<u8>>,
+    pub scc_pair: Vec<Vec<u32>>,
+    pub scc_prices: Vec<Vec<u128>>,
+    pub scc_timestamp: Vec<Vec<u128>>,
+    pub scc_decimals: Vec<Vec<u16>>,
+    pub scc_qc: Vec<Vec<u8>>,
+    pub scc_round: Vec<u64>,
+    pub scc_id: Vec<Vec<u8>>,
+    pub scc_member_index: Vec<u64>,
+    pub scc_committee_index: Vec<u64>,
+
+    // Index maps tying clusters to transactions and batches, and the vote signatures.
+    pub batch_idx: Vec<u64>,
+    pub txn_idx: Vec<u64>,
+    pub cluster_idx: Vec<u64>,
+    pub sig: Vec<Vec<u8>>,
+    pub pair_mask: Vec<Vec<bool>>,
+}
+
+impl PullResponseAptos {
+    /// Decodes every BCS field of the proof. Fails with [`DecodeError::MalformedField`] naming
+    /// the first field that does not decode.
+    pub fn parse(&self) -> Result<AptosProof, DecodeError> {
+        macro_rules! decode {
+            ($field:ident) => {
+                bcs::from_bytes(&self.$field).map_err(|source| DecodeError::MalformedField {
+                    field: stringify!($field),
+                    source,
+                })?
+            };
+        }
+
+        Ok(AptosProof {
+            pair_indexes: self.pair_indexes.clone(),
+            dkg_object: self.dkg_object.clone(),
+            oracle_holder_object: self.oracle_holder_object.clone(),
+            vote_smr_block_round: decode!(vote_smr_block_round),
+            vote_smr_block_timestamp: decode!(vote_smr_block_timestamp),
+            vote_smr_block_author: decode!(vote_smr_block_author),
+            vote_smr_block_qc_hash: decode!(vote_smr_block_qc_hash),
+            vote_smr_block_batch_hashes: decode!(vote_smr_block_batch_hashes),
+            vote_round: decode!(vote_round),
+            min_batch_protocol: decode!(min_batch_protocol),
+            min_batch_txn_hashes: decode!(min_batch_txn_hashes),
+            min_txn_cluster_hashes: decode!(min_txn_cluster_hashes),
+            min_txn_sender: decode!(min_txn_sender),
+            min_txn_protocol: decode!(min_txn_protocol),
+            min_txn_tx_sub_type: decode!(min_txn_tx_sub_type),
+            scc_data_hash: decode!(scc_data_hash),
+            scc_pair: decode!(scc_pair),
+            scc_prices: decode!(scc_prices),
+            scc_timestamp: decode!(scc_timestamp),
+            scc_decimals: decode!(scc_decimals),
+            scc_qc: decode!(scc_qc),
+            scc_round: decode!(scc_round),
+            scc_id: decode!(scc_id),
+            scc_member_index: decode!(scc_member_index),
+            scc_committee_index: decode!(scc_committee_index),
+            batch_idx: decode!(batch_idx),
+            txn_idx: decode!(txn_idx),
+            cluster_idx: decode!(cluster_idx),
+            sig: decode!(sig),
+            pair_mask: decode!(pair_mask),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A proof of the single pair 9, with every other field an empty BCS vector.
+    fn response() -> PullResponseAptos {
+        let empty = || vec![0u8];
+        PullResponseAptos {
+            pair_indexes: vec![9],
+            dkg_object: "0xd1".to_string(),
+            oracle_holder_object: "0x0e".to_string(),
+            vote_smr_block_round: empty(),
+            vote_smr_block_timestamp: empty(),
+            vote_smr_block_author: empty(),
+            vote_smr_block_qc_hash: empty(),
+            vote_smr_block_batch_hashes: empty(),
+            vote_round: empty(),
+            min_batch_protocol: empty(),
+            min_batch_txn_hashes: empty(),
+            min_txn_cluster_hashes: empty(),
+            min_txn_sender: empty(),
+            min_txn_protocol: empty(),
+            min_txn_tx_sub_type: empty(),
+            scc_data_hash: empty(),
+            scc_timestamp: empty(),
+            scc_decimals: empty(),
+            scc_qc: empty(),
+            scc_id: empty(),
+            scc_member_index: empty(),
+            scc_committee_index: empty(),
+            batch_idx: empty(),
+            txn_idx: empty(),
+            cluster_idx: empty(),
+            sig: empty(),
+            scc_pair: bcs::to_bytes(&vec![vec![9u32]]).unwrap(),
+            scc_prices: bcs::to_bytes(&vec![vec![5u128]]).unwrap(),
+            scc_round: bcs::to_bytes(&vec![42u64]).unwrap(),
+            pair_mask: bcs::to_bytes(&vec![vec![true]]).unwrap(),
+        }
+    }
+
+    fn malformed_field(response: &PullResponseAptos) -> &'static str {
+        match response.parse() {
+            Err(DecodeError::MalformedField { field, .. }) => field,
+            other => panic!("expected a malformed field, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_decodes_every_field() {
+        let proof = response().parse().unwrap();
+        assert_eq!(proof.pair_indexes, [9]);
+        assert_eq!(proof.dkg_object, "0xd1");
+        assert_eq!(proof.scc_pair, [[9]]);
+        assert_eq!(proof.scc_prices, [[5]]);
+        assert_eq!(proof.scc_round, [42]);
+        assert_eq!(proof.pair_mask, [[true]]);
+        assert!(proof.vote_round.is_empty());
+        assert!(proof.sig.is_empty());
+    }
+
+    #[test]
+    fn parse_names_the_first_empty_field() {
+        assert_eq!(
+            malformed_field(&PullResponseAptos::default()),
+            "vote_smr_block_round"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_truncated_and_trailing_bytes() {
+        let mut truncated = response();
+        truncated.scc_prices.pop();
+        assert_eq!(malformed_field(&truncated), "scc_prices");
+
+        let mut trailing = response();
+        trailing.sig.push(0);
+        assert_eq!(malformed_field(&trailing), "sig");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::errors::UnknownChainType;
+use crate::pull_service::PullRequest;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Chains the pull service can build proofs for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChainType {
+    Aptos,
+    Evm,
+    Sui,
+}
+
+impl ChainType {
+    /// The exact string the server expects in `PullRequest::chain_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChainType::Aptos => "aptos",
+            ChainType::Evm => "evm",
+            ChainType::Sui => "sui",
+        }
+    }
+}
+
+impl fmt::Display for ChainType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChainType {
+    type Err = UnknownChainType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "aptos" => Ok(ChainType::Aptos),
+            "evm" => Ok(ChainType::Evm),
+            "sui" => Ok(ChainType::Sui),
+            _ => Err(UnknownChainType(s.to_string())),
+        }
+    }
+}
+
+impl PullRequest {
+    pub fn new(pair_indexes: Vec<u32>, chain_type: ChainType) -> Self {
+        Self {
+            pair_indexes,
+            chain_type: chain_type.to_string(),
+            round: None,
+        }
+    }
+
+    /// The same request for the proof of a past `round` instead of the latest one.
+    ///
+    /// Servers that keep no history, or predate the field, ignore it and return the latest
+    /// proof; the gRPC client checks the round of the proof it gets and fails instead.
+    pub fn at_round(mut self, round: u64) -> Self {
+        self.round = Some(round);
+        self
+    }
+
+    /// The same request in a stable form: pairs sorted with duplicates dropped and a known chain
+    /// type lowercased, the round kept as is. Requests the server treats alike have equal
+    /// canonical forms.
+    pub fn canonical(&self) -> PullRequest {
+        let mut pair_indexes = self.pair_indexes.clone();
+        pair_indexes.sort_unstable();
+        pair_indexes.dedup();
+        let chain_type = match self.chain_type.parse::<ChainType>() {
+            Ok(chain_type) => chain_type.to_string(),
+            Err(_) => self.chain_type.clone(),
+        };
+        PullRequest {
+            pair_indexes,
+            chain_type,
+            round: self.round,
+        }
+    }
+
+    /// [`PullRequest::canonical`] as compact JSON with the fields in a fixed order, e.g.
+    /// `{"pair_indexes":[0,21],"chain_type":"evm"}`, for use as a cache key or signing input.
+    /// A round is appended as `"round":N` only when set, so requests for the latest proof keep
+    /// their existing form.
+    pub fn canonical_json(&self) -> String {
+        let canonical = self.canonical();
+        let pairs: Vec<String> = canonical.pair_indexes.iter().map(u32::to_string).collect();
+        let mut chain_type = String::with_capacity(canonical.chain_type.len());
+        for c in canonical.chain_type.chars() {
+            match c {
+                '"' => chain_type.push_str("\\\""),
+                '\\' => chain_type.push_str("\\\\"),
+                c if c.is_control() => chain_type.push_str(&format!("\\u{:04x}", c as u32)),
+                c => chain_type.push(c),
+            }
+        }
+        let round = match canonical.round {
+            Some(round) => format!(r#","round":{round}"#),
+            None => String::new(),
+        };
+        format!(
+            r#"{{"pair_indexes":[{}],"chain_type":"{}"{}}}"#,
+            pairs.join(","),
+            chain_type,
+            round
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn canonical_json_sorts_and_dedups_pairs() {
+        let request = PullRequest::new(vec![21, 0, 21, 5], ChainType::Evm);
+        assert_eq!(
+            request.canonical_json(),
+            r#"{"pair_indexes":[0,5,21],"chain_type":"evm"}"#
+        );
+    }
+
+    #[test]
+    fn canonical_json_appends_round_only_when_set() {
+        let request = PullRequest::new(vec![1], ChainType::Sui);
+        assert_eq!(
+            request.clone().at_round(42).canonical_json(),
+            r#"{"pair_indexes":[1],"chain_type":"sui","round":42}"#
+        );
+        assert_eq!(
+            request.canonical_json(),
+            r#"{"pair_indexes":[1],"chain_type":"sui"}"#
+        );
+    }
+
+    #[test]
+    fn canonical_json_lowercases_known_chain_types() {
+        let request = PullRequest {
+            pair_indexes: vec![],
+            chain_type: "APTOS".to_string(),
+            round: None,
+        };
+        assert_eq!(
+            request.canonical_json(),
+            r#"{"pair_indexes":[],"chain_type":"aptos"}"#
+        );
+    }
+
+    #[test]
+    fn canonical_json_escapes_unknown_chain_types() {
+        let request = PullRequest {
+            pair_indexes: vec![3],
+            chain_type: "a\"b\\c\n".to_string(),
+            round: None,
+        };
+        assert_eq!(
+            request.canonical_json(),
+            r#"{"pair_indexes":[3],"chain_type":"a\"b\\c\u000a"}"#
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use alloc::string::String;
+#[cfg(feature = "evm")]
+use alloc::string::ToString;
+#[cfg(feature = "evm")]
+use alloc::vec::Vec;
+#[cfg(feature = "evm")]
+use alloy_sol_types::Error as AbiError;
+use thiserror::Error;
+
+#[cfg(feature = "evm")]
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum VerificationError {
+    #[error("malformed proof: {0}")]
+    Malformed(String),
+    #[error("inconsistent proof: {0}")]
+    Inconsistent(String),
+    #[error("vote {vote} has no signature")]
+    MissingSignature { vote: usize },
+    #[error("vote {vote} was authored by {author}, which is not a committee member")]
+    UnknownAuthor { vote: usize, author: String },
+    #[error(
+        "proof was signed by the committee of epoch {proof_epoch}, expected epoch {expected_epoch}"
+    )]
+    CommitteeEpochMismatch {
+        proof_epoch: u64,
+        expected_epoch: u64,
+    },
+    #[error("proof round {round} is older than the minimum accepted round {min_round}")]
+    StaleRound { round: u64, min_round: u64 },
+    #[error("clusters of pairs {pairs:?} are not listed by their SMR transactions")]
+    NotIncluded { pairs: Vec<u32> },
+}
+
+#[cfg(feature = "evm")]
+impl From<AbiError> for VerificationError {
+    fn from(err: AbiError) -> Self {
+        VerificationError::Malformed(err.to_string())
+    }
+}
+
+#[cfg(feature = "evm")]
+impl From<DecodeError> for VerificationError {
+    fn from(err: DecodeError) -> Self {
+        match err {
+            DecodeError::Inconsistent(reason) => VerificationError::Inconsistent(reason),
+            err => VerificationError::Malformed(err.to_string()),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    #[cfg(feature = "evm")]
+    #[error(transparent)]
+    Abi(#[from] AbiError),
+    #[cfg(feature = "evm")]
+    #[error("clustersRaw[{index}] is not a valid SignedCoherentCluster: {source}")]
+    MalformedCluster {
+        index: usize,
+        #[source]
+        source: AbiError,
+    },
+    #[cfg(feature = "move")]
+    #[error(transparent)]
+    Bcs(#[from] bcs::Error),
+    #[cfg(feature = "move")]
+    #[error("{field} is not valid BCS: {source}")]
+    MalformedField {
+        field: &'static str,
+        #[source]
+        source: bcs::Error,
+    },
+    #[error("inconsistent proof: {0}")]
+    Inconsistent(String),
+    #[error("{field} value does not fit in its target type")]
+    Overflow { field: &'static str },
+}
+
+#[derive(Error, Debug)]
+#[error("unknown chain type: {0}")]
+pub struct UnknownChainType(pub String);
+
+/// A line [`PairRegistry::parse`](crate::pairs::PairRegistry::parse) could not read as
+/// `index,symbol`.
+#[derive(Error, Debug)]
+#[error("invalid pair table entry on line {line}")]
+pub struct InvalidPairTable {
+    pub line: usize,
+}
@@ -0,0 +1,24 @@
+//! Request and response types of the Supra pull service, the decoding of proofs for every chain
+//! and the offline structural checks of EVM proofs, without the networking client.
+//!
+//! Builds as `no_std` with `alloc` when the default `std` feature is off, e.g. to check proofs
+//! where neither a gRPC stack nor an async runtime is available.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "move")]
+pub mod aptos_proof;
+pub mod chain_type;
+pub mod errors;
+#[cfg(feature = "evm")]
+pub mod oracle_proof;
+pub mod pairs;
+pub mod price_data;
+#[cfg(feature = "evm")]
+pub mod verify;
+
+pub mod pull_service {
+    include!(concat!(env!("OUT_DIR"), "/pull_service.rs"));
+}
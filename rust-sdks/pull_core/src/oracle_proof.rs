@@ -0,0 +1,164 @@
+use crate::errors::DecodeError;
+use crate::pull_service::PullResponseEvm;
+use alloc::format;
+use alloc::vec::Vec;
+use alloy_primitives::U256;
+use alloy_sol_types::{self as sol_types, sol, SolValue};
+
+// Rust mirrors of `resources/oracleProof..json` and `resources/signedCoherentCluster.json`.
+// Integer fields are kept as `uint256` so out-of-range words surface as errors instead of panics.
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    struct OracleProof {
+        Vote[] votes;
+        uint256[2][] sigs;
+        SmrBatch[] smr_batches;
+        SmrTxn[] smr_txns;
+        bytes[] clusters_raw;
+        uint256[] batch_to_vote;
+        uint256[] txn_to_batch;
+        uint256[] cluster_to_txn;
+        uint256[] cluster_to_hash;
+        bool[] pair_mask;
+        uint256 pair_cnt;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Vote {
+        SmrBlock smr_block;
+        bytes8 round_le;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct SmrBlock {
+        uint256 round;
+        uint256 timestamp;
+        bytes32 author;
+        bytes32 qc_hash;
+        bytes32[] batch_hashes;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct SmrBatch {
+        bytes10 protocol;
+        bytes32[] txn_hashes;
+        uint256 batch_idx;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct SmrTxn {
+        bytes32[] cluster_hashes;
+        bytes32 sender;
+        bytes10 protocol;
+        bytes1 tx_sub_type;
+        uint256 txn_idx;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct SignedCoherentCluster {
+        CoherentCluster cc;
+        bytes qc;
+        uint256 round;
+        Origin origin;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct CoherentCluster {
+        bytes32 data_hash;
+        uint256[] pair;
+        uint256[] prices;
+        uint256[] timestamp;
+        uint256[] decimals;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Origin {
+        bytes32 public_key_identity;
+        uint256 pub_member_index;
+        uint256 committee_index;
+    }
+}
+
+impl OracleProof {
+    /// Decodes the ABI-encoded `proof_bytes` of a `PullResponseEvm`.
+    pub fn decode(proof_bytes: &[u8]) -> Result<Self, sol_types::Error> {
+        decode_tuple(proof_bytes)
+    }
+
+    /// Decodes every entry of `clusters_raw`, in order.
+    pub fn clusters(&self) -> Result<Vec<SignedCoherentCluster>, sol_types::Error> {
+        self.clusters_raw
+            .iter()
+            .map(|raw| decode_tuple(raw))
+            .collect()
+    }
+}
+
+/// `proof_bytes` with the nested `clustersRaw` blobs decoded as well.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedEvmProof {
+    pub proof: OracleProof,
+    /// `proof.clusters_raw`, decoded and in the same order. Each cluster's `origin` names the
+    /// committee and member that produced it.
+    pub clusters: Vec<SignedCoherentCluster>,
+    /// Who signed each vote of `proof.votes`, in the same order.
+    pub attestations: Vec<Attestation>,
+}
+
+/// A committee member's signature over one vote of a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attestation {
+    /// Public key identity of the member, the author of the voted SMR block.
+    pub signer: [u8; 32],
+    /// Round of the voted SMR block.
+    pub round: U256,
+    /// BLS signature over the vote, as the coordinates of a G1 point.
+    pub signature: [U256; 2],
+}
+
+impl PullResponseEvm {
+    /// Decodes the whole proof, for callers that build their own calldata, check it against an
+    /// on-chain committee or record who attested to it. Fails if the proof does not carry one
+    /// signature per vote.
+    pub fn parse(&self) -> Result<ParsedEvmProof, DecodeError> {
+        let proof = OracleProof::decode(&self.proof_bytes)?;
+        let clusters = proof
+            .clusters_raw
+            .iter()
+            .enumerate()
+            .map(|(index, raw)| {
+                decode_tuple(raw).map_err(|source| DecodeError::MalformedCluster { index, source })
+            })
+            .collect::<Result<_, _>>()?;
+        if proof.sigs.len() != proof.votes.len() {
+            return Err(DecodeError::Inconsistent(format!(
+                "{} signatures for {} votes",
+                proof.sigs.len(),
+                proof.votes.len()
+            )));
+        }
+        let attestations = proof
+            .votes
+            .iter()
+            .zip(&proof.sigs)
+            .map(|(vote, signature)| Attestation {
+                signer: vote.smr_block.author.0,
+                round: vote.smr_block.round,
+                signature: *signature,
+            })
+            .collect();
+        Ok(ParsedEvmProof {
+            proof,
+            clusters,
+            attestations,
+        })
+    }
+}
+
+/// Decodes data produced by solidity's `abi.encode(tuple)`, i.e. a single tuple parameter.
+fn decode_tuple<T: SolValue + From<<T::SolType as sol_types::SolType>::RustType>>(
+    bytes: &[u8],
+) -> Result<T, sol_types::Error> {
+    T::abi_decode(bytes)
+}
@@ -0,0 +1,146 @@
+//! Symbols of the trading pairs behind the numeric `pair_indexes`.
+//!
+//! The SDK ships no pair list. [`PairRegistry`] starts empty and holds whatever you put in it:
+//! the pairs Supra publishes for your network, loaded with [`PairRegistry::parse`] or added one
+//! at a time with [`PairRegistry::insert`].
+
+use crate::errors::InvalidPairTable;
+use crate::pull_service::pull_response::Resp;
+use crate::pull_service::{PullResponse, PullResponseAptos, PullResponseEvm, PullResponseSui};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Two-way lookup between pair indexes and symbols such as `BTC_USDT`, populated by the user.
+#[derive(Clone, Debug, Default)]
+pub struct PairRegistry {
+    by_index: BTreeMap<u32, String>,
+    by_symbol: BTreeMap<String, u32>,
+}
+
+impl PairRegistry {
+    /// Parses `index,symbol` lines. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(table: &str) -> Result<Self, InvalidPairTable> {
+        let mut pairs = PairRegistry::default();
+        for (line, entry) in table.lines().enumerate() {
+            let entry = entry.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            let (index, symbol) = entry
+                .split_once(',')
+                .and_then(|(index, symbol)| Some((index.trim().parse().ok()?, symbol.trim())))
+                .filter(|(_, symbol)| !symbol.is_empty())
+                .ok_or(InvalidPairTable { line: line + 1 })?;
+            pairs.insert(index, symbol);
+        }
+        Ok(pairs)
+    }
+
+    pub fn insert(&mut self, index: u32, symbol: &str) {
+        let symbol = symbol.to_ascii_uppercase();
+        self.by_symbol.insert(symbol.clone(), index);
+        self.by_index.insert(index, symbol);
+    }
+
+    pub fn symbol_for_index(&self, index: u32) -> Option<&str> {
+        self.by_index.get(&index).map(String::as_str)
+    }
+
+    /// Symbols are matched case-insensitively.
+    pub fn index_for_symbol(&self, symbol: &str) -> Option<u32> {
+        self.by_symbol.get(&symbol.to_ascii_uppercase()).copied()
+    }
+
+    /// Highest pair index in the registry.
+    pub fn max_index(&self) -> Option<u32> {
+        self.by_index.keys().next_back().copied()
+    }
+}
+
+macro_rules! pair_coverage {
+    ($($ty:ty),*) => {$(
+        impl $ty {
+            /// Number of pairs the server returned a proof for.
+            pub fn pair_count(&self) -> usize {
+                self.pair_indexes.len()
+            }
+
+            /// The pairs in `requested` that are not in `pair_indexes`, in request order, e.g.
+            /// because the server dropped a pair it does not support.
+            pub fn missing_pairs(&self, requested: &[u32]) -> Vec<u32> {
+                missing_pairs(&self.pair_indexes, requested)
+            }
+        }
+    )*};
+}
+
+pair_coverage!(PullResponseAptos, PullResponseEvm, PullResponseSui);
+
+impl PullResponse {
+    /// Number of pairs the server returned a proof for, zero if the response has no proof.
+    pub fn pair_count(&self) -> usize {
+        self.pair_indexes().len()
+    }
+
+    /// The pairs in `requested` that are not in the proof, in request order. All of them if
+    /// the response has no proof.
+    pub fn missing_pairs(&self, requested: &[u32]) -> Vec<u32> {
+        missing_pairs(self.pair_indexes(), requested)
+    }
+
+    fn pair_indexes(&self) -> &[u32] {
+        match &self.resp {
+            Some(Resp::Evm(evm)) => &evm.pair_indexes,
+            Some(Resp::Sui(sui)) => &sui.pair_indexes,
+            Some(Resp::Aptos(aptos)) => &aptos.pair_indexes,
+            None => &[],
+        }
+    }
+}
+
+fn missing_pairs(returned: &[u32], requested: &[u32]) -> Vec<u32> {
+    let returned: BTreeSet<u32> = returned.iter().copied().collect();
+    let mut seen = BTreeSet::new();
+    requested
+        .iter()
+        .copied()
+        .filter(|pair| !returned.contains(pair) && seen.insert(*pair))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn missing_pairs_keeps_request_order_and_drops_duplicates() {
+        let evm = PullResponseEvm {
+            pair_indexes: vec![3, 1],
+            proof_bytes: vec![],
+        };
+        assert_eq!(evm.pair_count(), 2);
+        assert_eq!(evm.missing_pairs(&[5, 1, 4, 5, 3]), [5, 4]);
+        assert!(evm.missing_pairs(&[]).is_empty());
+    }
+
+    #[test]
+    fn pull_response_covers_the_pairs_of_its_payload() {
+        let response = PullResponse {
+            resp: Some(Resp::Sui(PullResponseSui {
+                pair_indexes: vec![1, 2],
+                ..Default::default()
+            })),
+        };
+        assert_eq!(response.pair_count(), 2);
+        assert_eq!(response.missing_pairs(&[1, 2, 3]), [3]);
+    }
+
+    #[test]
+    fn pull_response_without_a_proof_misses_every_requested_pair() {
+        let response = PullResponse { resp: None };
+        assert_eq!(response.pair_count(), 0);
+        assert_eq!(response.missing_pairs(&[2, 2, 1]), [2, 1]);
+    }
+}
@@ -0,0 +1,546 @@
+use crate::errors::DecodeError;
+#[cfg(feature = "evm")]
+use crate::oracle_proof::{OracleProof, SignedCoherentCluster};
+#[cfg(feature = "evm")]
+use crate::pull_service::PullResponseEvm;
+#[cfg(feature = "move")]
+use crate::pull_service::{PullResponseAptos, PullResponseSui};
+#[cfg(any(feature = "evm", feature = "move"))]
+use alloc::format;
+#[cfg(any(feature = "evm", feature = "move"))]
+use alloc::string::ToString;
+#[cfg(any(feature = "evm", feature = "move"))]
+use alloc::vec::Vec;
+#[cfg(feature = "evm")]
+use alloy_primitives::U256;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+use core::time::Duration;
+pub use rust_decimal::Decimal;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Price of a single requested pair, as signed by the oracle committee.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriceData {
+    pub pair_index: u32,
+    pub price: u128,
+    pub decimals: u16,
+    pub round: u64,
+    pub timestamp: u128,
+}
+
+impl PriceData {
+    /// The price as a float, `price / 10^decimals`. Loses precision past about 15 significant
+    /// digits; use [`PriceData::as_decimal`] where that matters.
+    pub fn as_f64(&self) -> f64 {
+        // `f64::powi` needs `std`; the powers of ten a price can have are exact either way.
+        let scale = (0..self.decimals).fold(1f64, |scale, _| scale * 10.0);
+        self.price as f64 / scale
+    }
+
+    /// The exact price as a fixed-point decimal.
+    ///
+    /// Fails with [`DecodeError::Overflow`] if `price` does not fit the 96-bit mantissa or
+    /// `decimals` exceeds [`Decimal::MAX_SCALE`].
+    pub fn as_decimal(&self) -> Result<Decimal, DecodeError> {
+        if u32::from(self.decimals) > Decimal::MAX_SCALE {
+            return Err(DecodeError::Overflow { field: "decimals" });
+        }
+        i128::try_from(self.price)
+            .ok()
+            .and_then(|price| Decimal::try_from_i128_with_scale(price, self.decimals.into()).ok())
+            .ok_or(DecodeError::Overflow { field: "price" })
+    }
+
+    /// When the price was generated.
+    #[cfg(feature = "chrono")]
+    pub fn generated_at(&self) -> Option<DateTime<Utc>> {
+        to_date_time(self.timestamp)
+    }
+}
+
+/// Round and generation time of the oldest price in a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofAge {
+    pub round: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u128,
+}
+
+impl ProofAge {
+    #[cfg(any(feature = "evm", feature = "move"))]
+    fn of(prices: &[PriceData]) -> Result<Self, DecodeError> {
+        let round = prices.iter().map(|p| p.round).min();
+        let timestamp = prices.iter().map(|p| p.timestamp).min();
+        match (round, timestamp) {
+            (Some(round), Some(timestamp)) => Ok(Self { round, timestamp }),
+            _ => Err(DecodeError::Inconsistent(
+                "proof carries no prices".to_string(),
+            )),
+        }
+    }
+
+    /// Whether the oldest price was generated more than `max_age` ago.
+    #[cfg(feature = "std")]
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.is_stale_at(max_age, now)
+    }
+
+    /// [`ProofAge::is_stale`] against a clock the caller reads, `now` since the Unix epoch.
+    pub fn is_stale_at(&self, max_age: Duration, now: Duration) -> bool {
+        now.as_millis().saturating_sub(self.timestamp) > max_age.as_millis()
+    }
+
+    /// When the oldest price was generated.
+    #[cfg(feature = "chrono")]
+    pub fn generated_at(&self) -> Option<DateTime<Utc>> {
+        to_date_time(self.timestamp)
+    }
+}
+
+#[cfg(feature = "evm")]
+impl PullResponseEvm {
+    /// Round and timestamp of the oldest requested price, to check the proof is still fresh
+    /// before submitting it.
+    pub fn proof_age(&self) -> Result<ProofAge, DecodeError> {
+        ProofAge::of(&self.decode_prices()?)
+    }
+
+    /// Extracts the prices of the requested pairs from `proof_bytes`, in proof order.
+    pub fn decode_prices(&self) -> Result<Vec<PriceData>, DecodeError> {
+        let proof = OracleProof::decode(&self.proof_bytes)?;
+        prices_of(&proof, &proof.clusters()?)
+    }
+}
+
+/// The prices `proof` carries for its requested pairs, given its decoded `clusters`.
+#[cfg(feature = "evm")]
+pub(crate) fn prices_of(
+    proof: &OracleProof,
+    clusters: &[SignedCoherentCluster],
+) -> Result<Vec<PriceData>, DecodeError> {
+    let mut pair_mask = proof.pair_mask.iter();
+    let mut prices = Vec::new();
+    for (cluster, scc) in clusters.iter().enumerate() {
+        let cc = &scc.cc;
+        let pairs = cc.pair.len();
+        if cc.prices.len() != pairs || cc.timestamp.len() != pairs || cc.decimals.len() != pairs {
+            return Err(DecodeError::Inconsistent(format!(
+                "cluster {cluster} has {pairs} pairs but {} prices, {} timestamps and {} decimals",
+                cc.prices.len(),
+                cc.timestamp.len(),
+                cc.decimals.len()
+            )));
+        }
+        let round = narrow(scc.round, "round")?;
+        for pair in 0..pairs {
+            let requested = pair_mask.next().ok_or_else(|| {
+                DecodeError::Inconsistent("pairMask is shorter than the pair list".to_string())
+            })?;
+            if !requested {
+                continue;
+            }
+            prices.push(PriceData {
+                pair_index: narrow(cc.pair[pair], "pair")?,
+                price: narrow(cc.prices[pair], "prices")?,
+                decimals: narrow(cc.decimals[pair], "decimals")?,
+                round,
+                timestamp: narrow(cc.timestamp[pair], "timestamp")?,
+            });
+        }
+    }
+    Ok(prices)
+}
+
+#[cfg(feature = "evm")]
+fn narrow<T: TryFrom<U256>>(value: U256, field: &'static str) -> Result<T, DecodeError> {
+    T::try_from(value).map_err(|_| DecodeError::Overflow { field })
+}
+
+// The Aptos and Sui payloads share a layout: every `scc_*` field is a BCS vector with one entry
+// per cluster.
+#[cfg(feature = "move")]
+macro_rules! move_prices {
+    ($ty:ty) => {
+        impl $ty {
+            /// Round and timestamp of the oldest requested price, to check the proof is still
+            /// fresh before submitting it.
+            pub fn proof_age(&self) -> Result<ProofAge, DecodeError> {
+                ProofAge::of(&self.decode_prices()?)
+            }
+
+            /// Extracts the prices of the requested pairs from the BCS-encoded `scc_*` fields,
+            /// in proof order.
+            pub fn decode_prices(&self) -> Result<Vec<PriceData>, DecodeError> {
+                let pairs: Vec<Vec<u32>> = bcs::from_bytes(&self.scc_pair)?;
+                let prices: Vec<Vec<u128>> = bcs::from_bytes(&self.scc_prices)?;
+                let timestamps: Vec<Vec<u128>> = bcs::from_bytes(&self.scc_timestamp)?;
+                let decimals: Vec<Vec<u16>> = bcs::from_bytes(&self.scc_decimals)?;
+                let rounds: Vec<u64> = bcs::from_bytes(&self.scc_round)?;
+                let pair_mask: Vec<Vec<bool>> = bcs::from_bytes(&self.pair_mask)?;
+                cluster_prices(pairs, prices, timestamps, decimals, rounds, pair_mask)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "move")]
+move_prices!(PullResponseAptos);
+#[cfg(feature = "move")]
+move_prices!(PullResponseSui);
+
+#[cfg(feature = "move")]
+fn cluster_prices(
+    pairs: Vec<Vec<u32>>,
+    prices: Vec<Vec<u128>>,
+    timestamps: Vec<Vec<u128>>,
+    decimals: Vec<Vec<u16>>,
+    rounds: Vec<u64>,
+    pair_mask: Vec<Vec<bool>>,
+) -> Result<Vec<PriceData>, DecodeError> {
+    let clusters = pairs.len();
+    if prices.len() != clusters
+        || timestamps.len() != clusters
+        || decimals.len() != clusters
+        || rounds.len() != clusters
+        || pair_mask.len() != clusters
+    {
+        return Err(DecodeError::Inconsistent(format!(
+            "expected {clusters} clusters in every scc field and pair_mask"
+        )));
+    }
+
+    let mut result = Vec::new();
+    for cluster in 0..clusters {
+        let len = pairs[cluster].len();
+        if prices[cluster].len() != len
+            || timestamps[cluster].len() != len
+            || decimals[cluster].len() != len
+            || pair_mask[cluster].len() != len
+        {
+            return Err(DecodeError::Inconsistent(format!(
+                "cluster {cluster} fields disagree on the number of pairs"
+            )));
+        }
+        for pair in 0..len {
+            if !pair_mask[cluster][pair] {
+                continue;
+            }
+            result.push(PriceData {
+                pair_index: pairs[cluster][pair],
+                price: prices[cluster][pair],
+                decimals: decimals[cluster][pair],
+                round: rounds[cluster],
+                timestamp: timestamps[cluster][pair],
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// `None` if the millisecond timestamp is outside the range `DateTime` can represent.
+#[cfg(feature = "chrono")]
+fn to_date_time(timestamp_ms: u128) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(i64::try_from(timestamp_ms).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "evm")]
+    use crate::oracle_proof::{CoherentCluster, Origin};
+    #[cfg(any(feature = "evm", feature = "move"))]
+    use alloc::vec;
+    #[cfg(feature = "evm")]
+    use alloy_primitives::{Bytes, FixedBytes};
+    #[cfg(feature = "evm")]
+    use alloy_sol_types::SolValue;
+
+    /// One cluster of pairs 7 and 9, of which `pair_mask` picks the requested ones.
+    #[cfg(feature = "evm")]
+    fn evm(pair_mask: Vec<bool>) -> PullResponseEvm {
+        let cluster = SignedCoherentCluster {
+            cc: CoherentCluster {
+                data_hash: FixedBytes::ZERO,
+                pair: vec![U256::from(7), U256::from(9)],
+                prices: vec![U256::from(12345), U256::from(5)],
+                timestamp: vec![U256::from(1000), U256::from(2000)],
+                decimals: vec![U256::from(2), U256::from(8)],
+            },
+            qc: Bytes::new(),
+            round: U256::from(42),
+            origin: Origin {
+                public_key_identity: FixedBytes::ZERO,
+                pub_member_index: U256::ZERO,
+                committee_index: U256::ZERO,
+            },
+        };
+        let proof = OracleProof {
+            votes: vec![],
+            sigs: vec![],
+            smr_batches: vec![],
+            smr_txns: vec![],
+            clusters_raw: vec![cluster.abi_encode().into()],
+            batch_to_vote: vec![],
+            txn_to_batch: vec![],
+            cluster_to_txn: vec![],
+            cluster_to_hash: vec![],
+            pair_mask,
+            pair_cnt: U256::from(1),
+        };
+        PullResponseEvm {
+            pair_indexes: vec![9],
+            proof_bytes: proof.abi_encode(),
+        }
+    }
+
+    /// Clusters of pairs 7 and 9 in round 42 and of pair 11 in round 41; pair 7 is not requested.
+    #[cfg(feature = "move")]
+    fn sui() -> PullResponseSui {
+        PullResponseSui {
+            pair_indexes: vec![9, 11],
+            scc_pair: bcs::to_bytes(&vec![vec![7u32, 9], vec![11]]).unwrap(),
+            scc_prices: bcs::to_bytes(&vec![vec![12345u128, 5], vec![1]]).unwrap(),
+            scc_timestamp: bcs::to_bytes(&vec![vec![1000u128, 2000], vec![500]]).unwrap(),
+            scc_decimals: bcs::to_bytes(&vec![vec![2u16, 8], vec![0]]).unwrap(),
+            scc_round: bcs::to_bytes(&vec![42u64, 41]).unwrap(),
+            pair_mask: bcs::to_bytes(&vec![vec![false, true], vec![true]]).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn evm_decode_prices_returns_the_requested_pairs() {
+        assert_eq!(
+            evm(vec![false, true]).decode_prices().unwrap(),
+            [PriceData {
+                pair_index: 9,
+                price: 5,
+                decimals: 8,
+                round: 42,
+                timestamp: 2000,
+            }]
+        );
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn evm_decode_prices_rejects_empty_and_truncated_proofs() {
+        let mut response = evm(vec![false, true]);
+        response
+            .proof_bytes
+            .truncate(response.proof_bytes.len() - 32);
+        assert!(response.decode_prices().is_err());
+        response.proof_bytes.clear();
+        assert!(response.decode_prices().is_err());
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn evm_decode_prices_rejects_a_short_pair_mask() {
+        assert!(matches!(
+            evm(vec![true]).decode_prices(),
+            Err(DecodeError::Inconsistent(_))
+        ));
+    }
+
+    #[cfg(feature = "move")]
+    #[test]
+    fn move_decode_prices_returns_the_requested_pairs() {
+        assert_eq!(
+            sui().decode_prices().unwrap(),
+            [
+                PriceData {
+                    pair_index: 9,
+                    price: 5,
+                    decimals: 8,
+                    round: 42,
+                    timestamp: 2000,
+                },
+                PriceData {
+                    pair_index: 11,
+                    price: 1,
+                    decimals: 0,
+                    round: 41,
+                    timestamp: 500,
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "move")]
+    #[test]
+    fn move_decode_prices_rejects_empty_and_truncated_fields() {
+        assert!(matches!(
+            PullResponseSui::default().decode_prices(),
+            Err(DecodeError::Bcs(_))
+        ));
+        let mut truncated = sui();
+        truncated.scc_prices.pop();
+        assert!(matches!(
+            truncated.decode_prices(),
+            Err(DecodeError::Bcs(_))
+        ));
+    }
+
+    #[cfg(feature = "move")]
+    #[test]
+    fn move_decode_prices_rejects_fields_of_other_lengths() {
+        let mut response = sui();
+        response.scc_round = bcs::to_bytes(&vec![42u64]).unwrap();
+        assert!(matches!(
+            response.decode_prices(),
+            Err(DecodeError::Inconsistent(_))
+        ));
+
+        let mut response = sui();
+        response.scc_decimals = bcs::to_bytes(&vec![vec![2u16], vec![0]]).unwrap();
+        assert!(matches!(
+            response.decode_prices(),
+            Err(DecodeError::Inconsistent(_))
+        ));
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn evm_proof_age_ignores_pairs_not_requested() {
+        assert_eq!(
+            evm(vec![false, true]).proof_age().unwrap(),
+            ProofAge {
+                round: 42,
+                timestamp: 2000
+            }
+        );
+        assert!(matches!(
+            evm(vec![false, false]).proof_age(),
+            Err(DecodeError::Inconsistent(_))
+        ));
+    }
+
+    #[cfg(feature = "move")]
+    #[test]
+    fn move_proof_age_is_the_oldest_price() {
+        assert_eq!(
+            sui().proof_age().unwrap(),
+            ProofAge {
+                round: 41,
+                timestamp: 500
+            }
+        );
+    }
+
+    #[test]
+    fn is_stale_at_compares_the_age_with_max_age() {
+        let age = ProofAge {
+            round: 1,
+            timestamp: 10_000,
+        };
+        let now = Duration::from_secs(15);
+        assert!(!age.is_stale_at(Duration::from_secs(5), now));
+        assert!(age.is_stale_at(Duration::from_millis(4_999), now));
+    }
+
+    #[test]
+    fn is_stale_at_treats_timestamps_ahead_of_the_clock_as_fresh() {
+        let age = ProofAge {
+            round: 1,
+            timestamp: 10_000,
+        };
+        assert!(!age.is_stale_at(Duration::ZERO, Duration::from_secs(9)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn is_stale_reads_the_system_clock() {
+        let old = ProofAge {
+            round: 1,
+            timestamp: 0,
+        };
+        let future = ProofAge {
+            round: 1,
+            timestamp: u128::MAX,
+        };
+        assert!(old.is_stale(Duration::from_secs(1)));
+        assert!(!future.is_stale(Duration::ZERO));
+    }
+
+    fn price(price: u128, decimals: u16) -> PriceData {
+        PriceData {
+            pair_index: 0,
+            price,
+            decimals,
+            round: 0,
+            timestamp: 1_700_000_000_123,
+        }
+    }
+
+    #[test]
+    fn as_f64_scales_by_decimals() {
+        assert_eq!(price(12345, 2).as_f64(), 123.45);
+        assert_eq!(price(12345, 0).as_f64(), 12345.0);
+        assert_eq!(price(0, 8).as_f64(), 0.0);
+        assert!((price(12345, 40).as_f64() / 1.2345e-36 - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn as_decimal_is_exact() {
+        assert_eq!(
+            price(12345, 2).as_decimal().unwrap(),
+            Decimal::new(12345, 2)
+        );
+        assert_eq!(
+            price(1, Decimal::MAX_SCALE as u16).as_decimal().unwrap(),
+            Decimal::new(1, Decimal::MAX_SCALE)
+        );
+    }
+
+    #[test]
+    fn as_decimal_rejects_more_decimals_than_it_can_scale() {
+        assert!(matches!(
+            price(1, Decimal::MAX_SCALE as u16 + 1).as_decimal(),
+            Err(DecodeError::Overflow { field: "decimals" })
+        ));
+    }
+
+    #[test]
+    fn as_decimal_rejects_prices_beyond_96_bits() {
+        assert!(price((1 << 96) - 1, 0).as_decimal().is_ok());
+        for too_large in [1 << 96, u128::MAX] {
+            assert!(matches!(
+                price(too_large, 0).as_decimal(),
+                Err(DecodeError::Overflow { field: "price" })
+            ));
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn generated_at_converts_milliseconds_since_the_epoch() {
+        let expected = DateTime::from_timestamp_millis(1_700_000_000_123).unwrap();
+        assert_eq!(price(1, 0).generated_at(), Some(expected));
+        let age = ProofAge {
+            round: 1,
+            timestamp: 1_700_000_000_123,
+        };
+        assert_eq!(age.generated_at(), Some(expected));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn generated_at_is_none_outside_the_range_of_date_time() {
+        for timestamp in [i64::MAX as u128, u128::MAX] {
+            let age = ProofAge {
+                round: 1,
+                timestamp,
+            };
+            assert_eq!(age.generated_at(), None);
+        }
+    }
+}
@@ -0,0 +1,476 @@
+use crate::errors::VerificationError;
+use crate::oracle_proof::{OracleProof, SignedCoherentCluster};
+use crate::price_data::{prices_of, PriceData};
+use crate::pull_service::PullResponseEvm;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, U256};
+
+/// Checks the structure of a proof offline, to catch malformed proofs before paying gas to
+/// submit them.
+///
+/// Decodes `proof_bytes`, requires every vote to carry a signature and be authored by a member
+/// of `committee`, rejects proofs whose latest round is older than `min_round`, and checks the
+/// index maps tying clusters to transactions, batches and votes.
+///
+/// The BLS signatures are not verified, so a proof that passes is not authenticated: anyone can
+/// build one. Only the pull contract establishes that the committee signed it.
+pub fn check_structure(
+    response: &PullResponseEvm,
+    committee: &[[u8; 32]],
+    min_round: u64,
+) -> Result<(), VerificationError> {
+    let proof = OracleProof::decode(&response.proof_bytes)?;
+    check(&proof, &proof.clusters()?, committee, None, min_round)
+}
+
+/// Like [`check_structure`], but first requires every cluster to come from the committee of
+/// `epoch`, the `committee_index` its `origin` records, which `committee` must be a snapshot of.
+/// Fails with [`VerificationError::CommitteeEpochMismatch`] otherwise, e.g. after the committee
+/// rotated and the snapshot needs refreshing.
+pub fn check_structure_for_epoch(
+    response: &PullResponseEvm,
+    epoch: u64,
+    committee: &[[u8; 32]],
+    min_round: u64,
+) -> Result<(), VerificationError> {
+    let proof = OracleProof::decode(&response.proof_bytes)?;
+    check(
+        &proof,
+        &proof.clusters()?,
+        committee,
+        Some(epoch),
+        min_round,
+    )
+}
+
+/// Runs [`check_structure`] and [`check_cluster_inclusion`] and returns the prices of the
+/// requested pairs, in proof order, decoding `proof_bytes` once for all three. Fails with
+/// [`VerificationError::NotIncluded`] if any requested pair's cluster is not listed by its
+/// transaction.
+///
+/// The prices are unauthenticated: the vote signatures are not verified, so treat them as the
+/// server's claim until the pull contract has accepted the proof.
+pub fn check_and_decode(
+    response: &PullResponseEvm,
+    committee: &[[u8; 32]],
+    min_round: u64,
+) -> Result<Vec<PriceData>, VerificationError> {
+    checked_prices(response, committee, None, min_round)
+}
+
+/// [`check_and_decode`] with the committee epoch check of [`check_structure_for_epoch`].
+pub fn check_and_decode_for_epoch(
+    response: &PullResponseEvm,
+    epoch: u64,
+    committee: &[[u8; 32]],
+    min_round: u64,
+) -> Result<Vec<PriceData>, VerificationError> {
+    checked_prices(response, committee, Some(epoch), min_round)
+}
+
+fn checked_prices(
+    response: &PullResponseEvm,
+    committee: &[[u8; 32]],
+    epoch: Option<u64>,
+    min_round: u64,
+) -> Result<Vec<PriceData>, VerificationError> {
+    let proof = OracleProof::decode(&response.proof_bytes)?;
+    let clusters = proof.clusters()?;
+    check(&proof, &clusters, committee, epoch, min_round)?;
+    let report = inclusion(&proof, &clusters)?;
+    if !report.not_in_txn.is_empty() {
+        return Err(VerificationError::NotIncluded {
+            pairs: report.not_in_txn,
+        });
+    }
+    Ok(prices_of(&proof, &clusters)?)
+}
+
+fn check(
+    proof: &OracleProof,
+    clusters: &[SignedCoherentCluster],
+    committee: &[[u8; 32]],
+    epoch: Option<u64>,
+    min_round: u64,
+) -> Result<(), VerificationError> {
+    // Checked before the authors, who would all look unknown to a stale snapshot.
+    if let Some(expected_epoch) = epoch {
+        for scc in clusters {
+            let proof_epoch = to_u64(scc.origin.committee_index, "origin.committee_index")?;
+            if proof_epoch != expected_epoch {
+                return Err(VerificationError::CommitteeEpochMismatch {
+                    proof_epoch,
+                    expected_epoch,
+                });
+            }
+        }
+    }
+    if proof.votes.is_empty() {
+        return Err(VerificationError::Inconsistent(
+            "proof has no votes".to_string(),
+        ));
+    }
+    if proof.sigs.len() < proof.votes.len() {
+        return Err(VerificationError::MissingSignature {
+            vote: proof.sigs.len(),
+        });
+    }
+    if proof.sigs.len() > proof.votes.len() {
+        return Err(VerificationError::Inconsistent(format!(
+            "{} signatures for {} votes",
+            proof.sigs.len(),
+            proof.votes.len()
+        )));
+    }
+
+    let mut latest_round = 0;
+    for (vote, (v, sig)) in proof.votes.iter().zip(&proof.sigs).enumerate() {
+        // The all-zero placeholder of an unsigned vote.
+        if sig.iter().all(|coord| coord.is_zero()) {
+            return Err(VerificationError::MissingSignature { vote });
+        }
+        if !committee.contains(&v.smr_block.author.0) {
+            return Err(VerificationError::UnknownAuthor {
+                vote,
+                author: hex::encode(v.smr_block.author),
+            });
+        }
+        latest_round = latest_round.max(to_u64(v.smr_block.round, "smrBlock.round")?);
+    }
+    if latest_round < min_round {
+        return Err(VerificationError::StaleRound {
+            round: latest_round,
+            min_round,
+        });
+    }
+
+    check_links(
+        &proof.batch_to_vote,
+        proof.smr_batches.len(),
+        proof.votes.len(),
+        "batchToVote",
+    )?;
+    check_links(
+        &proof.txn_to_batch,
+        proof.smr_txns.len(),
+        proof.smr_batches.len(),
+        "txnToBatch",
+    )?;
+    check_links(
+        &proof.cluster_to_txn,
+        proof.clusters_raw.len(),
+        proof.smr_txns.len(),
+        "clusterToTxn",
+    )?;
+    if proof.cluster_to_hash.len() != proof.clusters_raw.len() {
+        return Err(VerificationError::Inconsistent(format!(
+            "clusterToHash has {} entries for {} clusters",
+            proof.cluster_to_hash.len(),
+            proof.clusters_raw.len()
+        )));
+    }
+    for (cluster, (txn, hash)) in proof
+        .cluster_to_txn
+        .iter()
+        .zip(&proof.cluster_to_hash)
+        .enumerate()
+    {
+        let hashes = proof.smr_txns[txn.to::<usize>()].cluster_hashes.len();
+        if *hash >= U256::from(hashes) {
+            return Err(VerificationError::Inconsistent(format!(
+                "cluster {cluster} points at hash {hash} of a transaction with {hashes} cluster hashes"
+            )));
+        }
+    }
+
+    let pairs: usize = clusters.iter().map(|scc| scc.cc.pair.len()).sum();
+    if pairs != proof.pair_mask.len() {
+        return Err(VerificationError::Inconsistent(format!(
+            "pairMask has {} entries for {pairs} pairs",
+            proof.pair_mask.len()
+        )));
+    }
+    let requested = proof
+        .pair_mask
+        .iter()
+        .filter(|requested| **requested)
+        .count();
+    if U256::from(requested) != proof.pair_cnt {
+        return Err(VerificationError::Inconsistent(format!(
+            "pairCnt is {} but pairMask selects {requested} pairs",
+            proof.pair_cnt
+        )));
+    }
+
+    Ok(())
+}
+
+/// Requested pairs of a proof, split by whether their cluster hash is listed by the SMR
+/// transaction it points at.
+///
+/// Only that first link is covered: nothing says the transaction itself is part of a batch or
+/// block the committee voted for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InclusionReport {
+    pub in_txn: Vec<u32>,
+    pub not_in_txn: Vec<u32>,
+}
+
+/// Checks offline, for every requested pair, that the cluster carrying its price is listed by
+/// the SMR transaction it claims: `keccak256(clustersRaw[i])` must equal the `clusterHashes`
+/// entry that `clusterToTxn` and `clusterToHash` point at, as the pull contract recomputes it.
+///
+/// Pairs in a cluster with a dangling index or hash mismatch are reported in `not_in_txn`. The
+/// transaction to batch and batch to block links are not hashed here, and the signatures above
+/// them are not verified either, so this does not establish that the committee signed the
+/// prices; only the pull contract does.
+pub fn check_cluster_inclusion(
+    response: &PullResponseEvm,
+) -> Result<InclusionReport, VerificationError> {
+    let proof = OracleProof::decode(&response.proof_bytes)?;
+    inclusion(&proof, &proof.clusters()?)
+}
+
+fn inclusion(
+    proof: &OracleProof,
+    clusters: &[SignedCoherentCluster],
+) -> Result<InclusionReport, VerificationError> {
+    let mut pair_mask = proof.pair_mask.iter();
+    let mut report = InclusionReport::default();
+    for (cluster, (raw, scc)) in proof.clusters_raw.iter().zip(clusters).enumerate() {
+        let committed = proof
+            .cluster_to_txn
+            .get(cluster)
+            .zip(proof.cluster_to_hash.get(cluster))
+            .and_then(|(txn, hash)| {
+                let txn = proof.smr_txns.get(usize::try_from(*txn).ok()?)?;
+                txn.cluster_hashes.get(usize::try_from(*hash).ok()?)
+            });
+        let included = committed == Some(&keccak256(raw));
+        for pair in &scc.cc.pair {
+            let requested = pair_mask.next().ok_or_else(|| {
+                VerificationError::Inconsistent(
+                    "pairMask is shorter than the pair list".to_string(),
+                )
+            })?;
+            if !requested {
+                continue;
+            }
+            let pair = u32::try_from(*pair).map_err(|_| {
+                VerificationError::Malformed(format!("pair {pair} does not fit in uint32"))
+            })?;
+            if included {
+                report.in_txn.push(pair);
+            } else {
+                report.not_in_txn.push(pair);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Ensures `links` has one entry per item and every entry indexes into a list of `targets`.
+fn check_links(
+    links: &[U256],
+    items: usize,
+    targets: usize,
+    name: &str,
+) -> Result<(), VerificationError> {
+    if links.len() != items {
+        return Err(VerificationError::Inconsistent(format!(
+            "{name} has {} entries for {items} items",
+            links.len()
+        )));
+    }
+    if let Some(link) = links.iter().find(|link| **link >= U256::from(targets)) {
+        return Err(VerificationError::Inconsistent(format!(
+            "{name} entry {link} is out of range for {targets} targets"
+        )));
+    }
+    Ok(())
+}
+
+fn to_u64(value: U256, field: &str) -> Result<u64, VerificationError> {
+    if value > U256::from(u64::MAX) {
+        return Err(VerificationError::Malformed(format!(
+            "{field} does not fit in uint64"
+        )));
+    }
+    Ok(value.to())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle_proof::{CoherentCluster, Origin, SmrBatch, SmrBlock, SmrTxn, Vote};
+    use alloc::vec;
+    use alloy_primitives::FixedBytes;
+    use alloy_sol_types::SolValue;
+
+    const AUTHOR: [u8; 32] = [3; 32];
+
+    /// One vote of round 50 over one cluster of pairs 7 and 9 from the committee of epoch 5,
+    /// with only pair 9 requested.
+    fn proof() -> OracleProof {
+        let scc = SignedCoherentCluster {
+            cc: CoherentCluster {
+                data_hash: FixedBytes::ZERO,
+                pair: vec![U256::from(7), U256::from(9)],
+                prices: vec![U256::from(123), U256::from(5)],
+                timestamp: vec![U256::from(1000), U256::from(1000)],
+                decimals: vec![U256::from(2), U256::from(8)],
+            },
+            qc: vec![1u8].into(),
+            round: U256::from(42),
+            origin: Origin {
+                public_key_identity: FixedBytes::ZERO,
+                pub_member_index: U256::ZERO,
+                committee_index: U256::from(5),
+            },
+        };
+        let raw = scc.abi_encode();
+        OracleProof {
+            votes: vec![Vote {
+                smr_block: SmrBlock {
+                    round: U256::from(50),
+                    timestamp: U256::ZERO,
+                    author: AUTHOR.into(),
+                    qc_hash: FixedBytes::ZERO,
+                    batch_hashes: vec![],
+                },
+                round_le: FixedBytes::ZERO,
+            }],
+            sigs: vec![[U256::from(1), U256::from(2)]],
+            smr_batches: vec![SmrBatch {
+                protocol: FixedBytes::ZERO,
+                txn_hashes: vec![],
+                batch_idx: U256::ZERO,
+            }],
+            smr_txns: vec![SmrTxn {
+                cluster_hashes: vec![keccak256(&raw)],
+                sender: FixedBytes::ZERO,
+                protocol: FixedBytes::ZERO,
+                tx_sub_type: FixedBytes::ZERO,
+                txn_idx: U256::ZERO,
+            }],
+            clusters_raw: vec![raw.into()],
+            batch_to_vote: vec![U256::ZERO],
+            txn_to_batch: vec![U256::ZERO],
+            cluster_to_txn: vec![U256::ZERO],
+            cluster_to_hash: vec![U256::ZERO],
+            pair_mask: vec![false, true],
+            pair_cnt: U256::from(1),
+        }
+    }
+
+    fn response(proof: &OracleProof) -> PullResponseEvm {
+        PullResponseEvm {
+            pair_indexes: vec![9],
+            proof_bytes: proof.abi_encode(),
+        }
+    }
+
+    #[test]
+    fn check_structure_accepts_a_well_formed_proof() {
+        assert!(check_structure(&response(&proof()), &[AUTHOR], 50).is_ok());
+    }
+
+    #[test]
+    fn check_structure_rejects_undecodable_bytes() {
+        let response = PullResponseEvm {
+            pair_indexes: vec![9],
+            proof_bytes: vec![0xab; 64],
+        };
+        assert!(matches!(
+            check_structure(&response, &[AUTHOR], 0),
+            Err(VerificationError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn check_structure_rejects_stale_rounds() {
+        assert!(matches!(
+            check_structure(&response(&proof()), &[AUTHOR], 51),
+            Err(VerificationError::StaleRound {
+                round: 50,
+                min_round: 51
+            })
+        ));
+    }
+
+    #[test]
+    fn check_structure_rejects_unsigned_votes_and_unknown_authors() {
+        let mut unsigned = proof();
+        unsigned.sigs = vec![[U256::ZERO, U256::ZERO]];
+        assert!(matches!(
+            check_structure(&response(&unsigned), &[AUTHOR], 0),
+            Err(VerificationError::MissingSignature { vote: 0 })
+        ));
+        assert!(matches!(
+            check_structure(&response(&proof()), &[[4; 32]], 0),
+            Err(VerificationError::UnknownAuthor { vote: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn check_structure_for_epoch_rejects_other_committees() {
+        assert!(check_structure_for_epoch(&response(&proof()), 5, &[AUTHOR], 0).is_ok());
+        assert!(matches!(
+            check_structure_for_epoch(&response(&proof()), 6, &[AUTHOR], 0),
+            Err(VerificationError::CommitteeEpochMismatch {
+                proof_epoch: 5,
+                expected_epoch: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn check_structure_rejects_dangling_links() {
+        let mut dangling = proof();
+        dangling.txn_to_batch = vec![U256::from(1)];
+        assert!(matches!(
+            check_structure(&response(&dangling), &[AUTHOR], 0),
+            Err(VerificationError::Inconsistent(_))
+        ));
+    }
+
+    #[test]
+    fn check_cluster_inclusion_reports_requested_pairs() {
+        let report = check_cluster_inclusion(&response(&proof())).unwrap();
+        assert_eq!(report.in_txn, [9]);
+        assert!(report.not_in_txn.is_empty());
+
+        let mut unlisted = proof();
+        unlisted.smr_txns[0].cluster_hashes = vec![FixedBytes::ZERO];
+        let report = check_cluster_inclusion(&response(&unlisted)).unwrap();
+        assert!(report.in_txn.is_empty());
+        assert_eq!(report.not_in_txn, [9]);
+    }
+
+    #[test]
+    fn check_and_decode_returns_the_requested_prices() {
+        let prices = check_and_decode(&response(&proof()), &[AUTHOR], 0).unwrap();
+        assert_eq!(
+            prices,
+            [PriceData {
+                pair_index: 9,
+                price: 5,
+                decimals: 8,
+                round: 42,
+                timestamp: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn check_and_decode_rejects_pairs_outside_the_transaction() {
+        let mut unlisted = proof();
+        unlisted.smr_txns[0].cluster_hashes = vec![FixedBytes::ZERO];
+        assert!(matches!(
+            check_and_decode(&response(&unlisted), &[AUTHOR], 0),
+            Err(VerificationError::NotIncluded { pairs }) if pairs == [9]
+        ));
+    }
+}
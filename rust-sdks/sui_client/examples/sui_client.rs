@@ -1,3 +1,4 @@
+use sui_pull_client::chain_type::ChainType;
 use sui_pull_client::pull_service::pull_response::Resp;
 use sui_pull_client::sui_connector::{invoke_sui_chain, SuiConfig, SuiConnector};
 use sui_pull_client::{pull_service, Client};
@@ -10,7 +11,8 @@ async fn main() {
     // Create a PullRequest
     let request = pull_service::PullRequest {
         pair_indexes: vec![0, 21, 61, 49], // Set the pair indexes as an array
-        chain_type: "sui".to_string(),
+        chain_type: ChainType::Sui.to_string(),
+        round: None, // Set a past round to fetch, or None for the latest
     };
 
     // Call the get_proof function and handle the result
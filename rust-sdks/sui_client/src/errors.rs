@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+pub use pull_client::errors::{ClientError, DecodeError, InvalidPairTable, UnknownChainType};
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum ConnectorError {
@@ -9,4 +11,8 @@ pub enum ConnectorError {
     SuiTransaction(String),
     #[error("invalid secret key")]
     InvalidSecretKey,
+    #[error("missing environment variables: {}", .0.join(", "))]
+    MissingEnv(Vec<&'static str>),
+    #[error("invalid value in environment variable {0}")]
+    InvalidEnv(&'static str),
 }
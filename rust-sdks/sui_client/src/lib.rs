@@ -1,33 +1,19 @@
-use crate::pull_service::pull_service_client::PullServiceClient;
-use pull_service::{PullRequest, PullResponse};
+//! The Supra pull service client for Sui: the shared [`pull_client`] with the sui
+//! connector.
 
-pub mod errors;
-pub mod sui_connector;
-pub mod pull_service {
-    tonic::include_proto!("pull_service");
-}
-
-pub struct Client {
-    client: PullServiceClient<tonic::transport::Channel>,
-}
+#[cfg(feature = "blocking")]
+pub use pull_client::blocking;
+pub use pull_client::proxy;
+#[cfg(feature = "test-util")]
+pub use pull_client::test_util;
+pub use pull_client::{
+    builder, env, fetch_proof, oracle_client, pull_service, retry, Client, OracleClient,
+    PartialProof, ProofEnvelope, ProofResponse, SuiClient, DEFAULT_CHUNK_SIZE, DEFAULT_CONCURRENCY,
+};
 
-impl Client {
-    pub async fn new(address: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = PullServiceClient::connect(address).await?;
-        Ok(Self { client })
-    }
-
-    pub async fn get_proof(
-        &mut self,
-        request: &PullRequest,
-    ) -> Result<PullResponse, Box<dyn std::error::Error>> {
-        match self
-            .client
-            .get_proof(tonic::Request::new(request.clone()))
-            .await
-        {
-            Ok(response) => Ok(response.into_inner()),
-            Err(status) => Err(Box::new(status)),
-        }
-    }
-}
+#[cfg(feature = "sui")]
+pub mod sui_connector;
+pub use pull_core::chain_type;
+pub mod errors;
+pub use pull_core::pairs;
+pub use pull_core::price_data;
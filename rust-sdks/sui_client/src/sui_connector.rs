@@ -1,17 +1,21 @@
+use crate::env::Env;
 use crate::errors::ConnectorError;
 use crate::pull_service::PullResponseSui;
+use async_trait::async_trait;
 use shared_crypto::intent::Intent;
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::{fmt::Display, future::Future};
 use sui_keys::keystore::{AccountKeystore, InMemKeystore};
 use sui_sdk::json::{MoveTypeLayout, SuiJsonValue};
 use sui_sdk::rpc_types::{SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions};
 use sui_sdk::{SuiClient, SuiClientBuilder};
 use sui_types::base_types::ObjectID;
 use sui_types::base_types::SuiAddress;
-use sui_types::crypto::SuiKeyPair;
+use sui_types::crypto::{Signature, SuiKeyPair};
 use sui_types::transaction::{Transaction, TransactionData};
 
 const MODULE: &str = "<CONTRACT MODULE>"; // Module name of your contract. Ex. pull_example
@@ -73,6 +77,11 @@ pub async fn invoke_sui_chain(payload: PullResponseSui, sui_connector: SuiConnec
         SuiJsonValue::from_bcs_bytes(Some(&vec_vec_u8), &payload.sig).unwrap(),
         SuiJsonValue::from_bcs_bytes(Some(&vec_vec_bool), &payload.pair_mask).unwrap(),
     ];
+    // Verbose, but the exact arguments are what a failed call has to be reproduced with.
+    log::debug!(
+        "sui: calling {}::{MODULE}::{ENTRY} with {sui_arg:?}",
+        sui_connector.sc_addr
+    );
     let tx_data = sui_connector
         .client
         .with_sui(|sui_client| {
@@ -89,6 +98,23 @@ pub async fn invoke_sui_chain(payload: PullResponseSui, sui_connector: SuiConnec
         })
         .await
         .unwrap();
+    if sui_connector.dry_run {
+        let signed = sui_connector.signer.sign(&tx_data).await;
+        if let Err(err) = signed {
+            println!("{:?}", err);
+            return;
+        }
+        let dry_run = sui_connector
+            .client
+            .with_sui(|sui| sui.read_api().dry_run_transaction_block(tx_data.clone()))
+            .await;
+        println!("dry run, not executing transaction {}", tx_data.digest());
+        match dry_run {
+            Ok(dry_run) => println!("{:?}", dry_run.effects),
+            Err(err) => println!("{:?}", err),
+        }
+        return;
+    }
     let transaction = sui_connector.sign_and_execute_tx(tx_data).await;
     if let Ok(transaction) = transaction {
         let transaction = transaction.digest.to_string();
@@ -99,10 +125,11 @@ pub async fn invoke_sui_chain(payload: PullResponseSui, sui_connector: SuiConnec
 }
 
 pub struct SuiConfig<'a> {
-    secret_key: &'a str,
-    client_url: &'a str,
-    sc_address: &'a str,
+    secret_key: Cow<'a, str>,
+    client_url: Cow<'a, str>,
+    sc_address: Cow<'a, str>,
     gas_budget: u64,
+    dry_run: bool,
 }
 
 impl<'a> SuiConfig<'a> {
@@ -113,19 +140,118 @@ impl<'a> SuiConfig<'a> {
         gas_budget: u64,
     ) -> Self {
         Self {
-            secret_key,
-            client_url,
-            sc_address,
+            secret_key: secret_key.into(),
+            client_url: client_url.into(),
+            sc_address: sc_address.into(),
             gas_budget,
+            dry_run: false,
         }
     }
+
+    /// Reads `SUI_SECRET_KEY`, `SUI_RPC_URL`, `SUI_CONTRACT_ADDRESS` and `SUI_GAS_BUDGET`.
+    /// Fails with [`ConnectorError::MissingEnv`] naming every unset variable.
+    pub fn from_env() -> Result<SuiConfig<'static>, ConnectorError> {
+        let mut env = Env::default();
+        let secret_key = env.required("SUI_SECRET_KEY");
+        let client_url = env.required("SUI_RPC_URL");
+        let sc_address = env.required("SUI_CONTRACT_ADDRESS");
+        let gas_budget = env.required("SUI_GAS_BUDGET");
+        env.finish().map_err(ConnectorError::MissingEnv)?;
+
+        let gas_budget = gas_budget
+            .parse()
+            .map_err(|_| ConnectorError::InvalidEnv("SUI_GAS_BUDGET"))?;
+        Ok(SuiConfig {
+            secret_key: secret_key.into(),
+            client_url: client_url.into(),
+            sc_address: sc_address.into(),
+            gas_budget,
+            dry_run: false,
+        })
+    }
+
+    /// Build, sign and dry-run every submission on the node but never execute it, e.g. to test a
+    /// deployment script against a mainnet config. [`invoke_sui_chain`] prints the digest the
+    /// transaction would have had and its simulated effects instead.
+    pub fn with_dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+}
+
+impl fmt::Debug for SuiConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SuiConfig")
+            .field("secret_key", &"***")
+            .field("client_url", &self.client_url)
+            .field("sc_address", &self.sc_address)
+            .field("gas_budget", &self.gas_budget)
+            .field("dry_run", &self.dry_run)
+            .finish()
+    }
 }
 
 pub struct SuiConnector {
     client: ClientWrapper,
     sc_addr: String,
-    secret_key: String,
+    signer: Arc<dyn Signer>,
     gas_budget: u64,
+    dry_run: bool,
+}
+
+/// Signs the connector's transactions, e.g. with a key held in a KMS or a hardware wallet.
+///
+/// [`PrivateKeySigner`] signs with an in-memory key, which is what [`SuiConnector::new`] uses.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// The address transactions are sent from.
+    fn address(&self) -> SuiAddress;
+
+    /// Signs `tx_data` with the Sui transaction intent.
+    async fn sign(&self, tx_data: &TransactionData) -> Result<Signature, ConnectorError>;
+}
+
+/// A [`Signer`] holding a Sui key pair in memory.
+pub struct PrivateKeySigner {
+    key_store: InMemKeystore,
+    address: SuiAddress,
+}
+
+impl PrivateKeySigner {
+    /// Parses a base64 encoded, flag prefixed key pair as exported by `sui keytool`.
+    pub fn from_secret_key(secret_key: &str) -> Result<Self, ConnectorError> {
+        let key_pair =
+            SuiKeyPair::from_str(secret_key).map_err(|_| ConnectorError::InvalidSecretKey)?;
+        let address: SuiAddress = (&key_pair.public()).into();
+
+        let mut key_store = InMemKeystore::default();
+        key_store
+            .add_key(key_pair)
+            .map_err(|_| ConnectorError::InvalidSecretKey)?;
+        Ok(Self { key_store, address })
+    }
+}
+
+impl fmt::Debug for PrivateKeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKeySigner")
+            .field("address", &self.address)
+            .field("key", &"***")
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Signer for PrivateKeySigner {
+    fn address(&self) -> SuiAddress {
+        self.address
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Result<Signature, ConnectorError> {
+        self.key_store
+            .sign_secure(&self.address, tx_data, Intent::sui_transaction())
+            .map_err(|err| ConnectorError::SuiTransaction(err.to_string()))
+    }
 }
 
 #[derive(Clone)]
@@ -137,48 +263,41 @@ pub struct ClientWrapper {
 
 impl SuiConnector {
     pub async fn new(conf: SuiConfig<'_>) -> Result<Self, ConnectorError> {
+        let signer = PrivateKeySigner::from_secret_key(&conf.secret_key)?;
+        Self::with_signer(conf, signer).await
+    }
+
+    /// Like [`SuiConnector::new`], but signs with `signer` instead of the secret key in `conf`,
+    /// which is ignored.
+    pub async fn with_signer(
+        conf: SuiConfig<'_>,
+        signer: impl Signer + 'static,
+    ) -> Result<Self, ConnectorError> {
         log::trace!("Start SuiConnector");
         let client = SuiClientBuilder::default()
-            .build(conf.client_url)
+            .build(conf.client_url.as_ref())
             .await
             .map_err(|_| ConnectorError::InvalidUrl)?;
 
         let sui_connector = Self {
             client: ClientWrapper::new(client, None),
             sc_addr: conf.sc_address.to_string(),
-            secret_key: conf.secret_key.to_string(),
+            signer: Arc::new(signer),
             gas_budget: conf.gas_budget,
+            dry_run: conf.dry_run,
         };
         Ok(sui_connector)
     }
 
     pub fn get_sui_address(&self) -> Result<SuiAddress, ConnectorError> {
-        let key_pair =
-            SuiKeyPair::from_str(&self.secret_key).map_err(|_| ConnectorError::InvalidSecretKey)?;
-        let sui_address: SuiAddress = (&key_pair.public()).into();
-        Ok(sui_address)
-    }
-
-    fn get_key_store(&self) -> Result<InMemKeystore, ConnectorError> {
-        let key_pair =
-            SuiKeyPair::from_str(&self.secret_key).map_err(|_| ConnectorError::InvalidSecretKey)?;
-
-        let mut key_store = InMemKeystore::default();
-        key_store
-            .add_key(key_pair)
-            .map_err(|_| ConnectorError::InvalidSecretKey)?;
-        Ok(key_store)
+        Ok(self.signer.address())
     }
 
     pub async fn sign_and_execute_tx(
         &self,
         tx_data: TransactionData,
     ) -> Result<SuiTransactionBlockResponse, ConnectorError> {
-        let key_store = self.get_key_store()?;
-        let owner = self.get_sui_address()?;
-        let signature = key_store
-            .sign_secure(&owner, &tx_data, Intent::sui_transaction())
-            .map_err(|err| ConnectorError::SuiTransaction(err.to_string()))?;
+        let signature = self.signer.sign(&tx_data).await?;
 
         let tx = Transaction::from_data(tx_data, Intent::sui_transaction(), vec![signature])
             .verify()
@@ -232,3 +351,24 @@ impl ClientWrapper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "AKi3TFXV3dXuIcTX2BtbxVd1tkDWyKZQzFOSQ4sr9j3M";
+
+    #[test]
+    fn config_debug_hides_secret_key() {
+        let conf = SuiConfig::new(
+            SECRET,
+            "https://fullnode.testnet.sui.io:443",
+            "0x1",
+            100_000_000,
+        );
+        for debug in [format!("{conf:?}"), format!("{conf:#?}")] {
+            assert!(!debug.contains(SECRET), "{debug}");
+            assert!(debug.contains("***"), "{debug}");
+        }
+    }
+}